@@ -0,0 +1,646 @@
+use crate::filtration::Filtration;
+use crate::geometry::{orthosphere, solve, squared_distance};
+use crate::simplex::Simplex;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Builds the alpha filtration of the points.
+///
+/// The filtration value of a simplex is the squared radius of the smallest empty
+/// circumsphere of it, i.e. the simplex enters the alpha complex when the squared alpha
+/// reaches the value. The vertices of the complex are the indices of `points`.
+/// The points should be in general position and of dimension 2 or 3.
+///
+/// # Example
+/// ```
+/// use teia::alpha::alpha_filtration;
+///
+/// let points = vec![vec![0.0, 0.0], vec![1.0, 0.0], vec![0.0, 1.0], vec![1.0, 1.0]];
+/// let filt = alpha_filtration(&points);
+/// assert_eq!(filt.len(), 4 + 5 + 2);
+/// ```
+pub fn alpha_filtration(points: &[Vec<f64>]) -> Filtration<Simplex, f64> {
+    weighted_alpha_filtration(points, &vec![0.0; points.len()])
+}
+
+/// Builds the weighted alpha filtration of the weighted points.
+///
+/// The weight of a point is the squared radius of the ball centered at it.
+/// The filtration value of a simplex is the squared radius of the smallest orthogonal sphere,
+/// so the values may be negative and the value of a vertex is the negation of its weight.
+/// The points which do not appear in the regular triangulation are not included.
+pub fn weighted_alpha_filtration(points: &[Vec<f64>], weights: &[f64]) -> Filtration<Simplex, f64> {
+    assert_eq!(points.len(), weights.len());
+    if points.is_empty() {
+        return Filtration::new();
+    }
+
+    let sphere = |simplex: &[usize]| {
+        let coords: Vec<&[f64]> = simplex.iter().map(|v| points[*v].as_slice()).collect();
+        let ws: Vec<f64> = simplex.iter().map(|v| weights[*v]).collect();
+        orthosphere(&coords, &ws)
+    };
+
+    // The faces of the regular triangulation grouped by dimension.
+    let mut faces: Vec<BTreeSet<Vec<usize>>> = Vec::new();
+    for cell in regular_triangulation(points, weights, true).into_iter() {
+        for face in subsets(&cell).into_iter() {
+            while faces.len() < face.len() {
+                faces.push(BTreeSet::new());
+            }
+            faces[face.len() - 1].insert(face);
+        }
+    }
+
+    // A face is attached to a coface if the vertex of the coface opposite to the face
+    // lies inside the smallest orthogonal sphere of the face. The value of an attached face
+    // is the minimum of the values of its cofaces, and so is the value of a face whose
+    // sphere cannot be computed because of rounding errors.
+    let mut values = BTreeMap::new();
+    let mut attached = BTreeSet::new();
+    let mut coface_min: BTreeMap<Vec<usize>, f64> = BTreeMap::new();
+    for dim in (0..faces.len()).rev() {
+        for simplex in faces[dim].iter() {
+            let value = match sphere(simplex) {
+                Some((_, radius2)) if !attached.contains(simplex) => radius2,
+                _ => match coface_min.get(simplex) {
+                    Some(value) => *value,
+                    None => continue,
+                },
+            };
+            values.insert(simplex.clone(), value);
+
+            if dim == 0 {
+                continue;
+            }
+            for i in 0..simplex.len() {
+                let mut face = simplex.clone();
+                let opposite = face.remove(i);
+                let entry = coface_min.entry(face.clone()).or_insert(value);
+                if value < *entry {
+                    *entry = value;
+                }
+                if let Some((c, r2)) = sphere(&face) {
+                    if squared_distance(&points[opposite], &c) - weights[opposite] < r2 {
+                        attached.insert(face);
+                    }
+                }
+            }
+        }
+    }
+
+    Filtration::from_unsorted(
+        values
+            .into_iter()
+            .map(|(vertices, value)| (Simplex::new(vertices), value))
+            .collect(),
+    )
+}
+
+/// Returns all the nonempty subsets of `set`.
+fn subsets(set: &[usize]) -> Vec<Vec<usize>> {
+    (1..(1usize << set.len()))
+        .map(|mask| {
+            set.iter()
+                .enumerate()
+                .filter(|(i, _)| mask & (1 << i) != 0)
+                .map(|(_, v)| *v)
+                .collect()
+        })
+        .collect()
+}
+
+/// The vertex at infinity of a triangulation
+///
+/// A cell containing it stands for a facet of the convex hull, and it is numbered after
+/// all the points so that it is the last vertex of a sorted cell.
+const INFINITE: usize = usize::MAX;
+
+struct Cell {
+    vertices: Vec<usize>,
+    center: Vec<f64>,
+    radius2: f64,
+    /// The outward normal of the hull facet of a cell containing the vertex at infinity
+    normal: Option<Vec<f64>>,
+}
+
+/// A triangulation of weighted points spanning the whole space
+struct Triangulation {
+    points: Vec<Vec<f64>>,
+    weights: Vec<f64>,
+    /// A point inside the convex hull of the points inserted so far
+    interior: Vec<f64>,
+    cells: Vec<Cell>,
+}
+
+impl Triangulation {
+    /// Creates the triangulation of a simplex of affinely independent points.
+    fn new(points: Vec<Vec<f64>>, weights: Vec<f64>, simplex: &[usize]) -> Self {
+        let dim = simplex.len() - 1;
+        let mut interior = vec![0.0; dim];
+        for v in simplex.iter() {
+            for (x, y) in interior.iter_mut().zip(points[*v].iter()) {
+                *x += y / simplex.len() as f64;
+            }
+        }
+
+        let mut triangulation = Triangulation {
+            points: points,
+            weights: weights,
+            interior: interior,
+            cells: Vec::new(),
+        };
+        let mut simplex = simplex.to_vec();
+        simplex.sort();
+        let mut cells = vec![simplex.clone()];
+        for i in 0..simplex.len() {
+            let mut cell = simplex.clone();
+            cell.remove(i);
+            cell.push(INFINITE);
+            cells.push(cell);
+        }
+        for cell in cells.into_iter() {
+            let cell = triangulation.make_cell(cell).expect("degenerate initial simplex");
+            triangulation.cells.push(cell);
+        }
+        triangulation
+    }
+
+    /// Computes the orthosphere of a cell, or the outward normal and the orthosphere of
+    /// the hull facet if the cell contains the vertex at infinity.
+    ///
+    /// It returns `None` if the cell is degenerate.
+    fn make_cell(&self, vertices: Vec<usize>) -> Option<Cell> {
+        let finite: Vec<usize> = vertices.iter().cloned().filter(|v| *v != INFINITE).collect();
+        let coords: Vec<&[f64]> = finite.iter().map(|v| self.points[*v].as_slice()).collect();
+        let weights: Vec<f64> = finite.iter().map(|v| self.weights[*v]).collect();
+        let (center, radius2) = orthosphere(&coords, &weights)?;
+
+        let normal = if finite.len() < vertices.len() {
+            let origin = coords[0];
+            let edges: Vec<Vec<f64>> = coords[1..]
+                .iter()
+                .map(|p| p.iter().zip(origin.iter()).map(|(x, o)| x - o).collect())
+                .collect();
+            // The cofactors of the edges are normal to the facet.
+            let mut normal: Vec<f64> = (0..origin.len())
+                .map(|j| {
+                    let minor = edges
+                        .iter()
+                        .map(|e| {
+                            e.iter()
+                                .enumerate()
+                                .filter(|(k, _)| *k != j)
+                                .map(|(_, x)| *x)
+                                .collect()
+                        })
+                        .collect();
+                    let sign = if j % 2 == 0 { 1.0 } else { -1.0 };
+                    sign * determinant(minor)
+                })
+                .collect();
+            let side = offset(&normal, origin, &self.interior);
+            if side == 0.0 {
+                return None;
+            } else if side > 0.0 {
+                for x in normal.iter_mut() {
+                    *x = -*x;
+                }
+            }
+            Some(normal)
+        } else {
+            None
+        };
+
+        Some(Cell {
+            vertices: vertices,
+            center: center,
+            radius2: radius2,
+            normal: normal,
+        })
+    }
+
+    /// Returns whether the point `p` is in conflict with the cell.
+    ///
+    /// A point is in conflict with a finite cell if its power with respect to the orthosphere
+    /// is negative. It is in conflict with a hull facet if it is beyond the facet, or if it
+    /// lies on the hyperplane of the facet and is in conflict with its orthosphere.
+    fn conflicts(&self, cell: &Cell, p: usize) -> bool {
+        let point = &self.points[p];
+        if let Some(normal) = cell.normal.as_ref() {
+            let side = offset(normal, &self.points[cell.vertices[0]], point);
+            if side != 0.0 {
+                return side > 0.0;
+            }
+        }
+        squared_distance(point, &cell.center) - self.weights[p] < cell.radius2
+    }
+
+    /// Inserts the point `p` by the Bowyer-Watson algorithm.
+    ///
+    /// It returns the other finite vertices of the new cells, which is empty if `p` is
+    /// redundant.
+    fn insert(&mut self, p: usize) -> Vec<usize> {
+        let (conflict, mut keep): (Vec<Cell>, Vec<Cell>) = std::mem::take(&mut self.cells)
+            .into_iter()
+            .partition(|cell| self.conflicts(cell, p));
+
+        let mut facets = BTreeMap::new();
+        for cell in conflict.iter() {
+            for i in 0..cell.vertices.len() {
+                let mut facet = cell.vertices.clone();
+                facet.remove(i);
+                *facets.entry(facet).or_insert(0) += 1;
+            }
+        }
+
+        let mut neighbors = BTreeSet::new();
+        for (mut facet, count) in facets.into_iter() {
+            if count == 1 {
+                neighbors.extend(facet.iter().cloned().filter(|v| *v != INFINITE));
+                facet.push(p);
+                facet.sort();
+                if let Some(cell) = self.make_cell(facet) {
+                    keep.push(cell);
+                }
+            }
+        }
+        self.cells = keep;
+        neighbors.into_iter().collect()
+    }
+
+    /// Removes the vertex `u` if it is redundant and re-triangulates its star.
+    ///
+    /// The star is filled with the cells of the regular triangulation of its link inside it,
+    /// and `u` is redundant if it is not in conflict with the cell containing it.
+    /// The vertices on the convex hull are never redundant.
+    fn remove_if_redundant(&mut self, u: usize) {
+        let (star, rest): (Vec<Cell>, Vec<Cell>) = std::mem::take(&mut self.cells)
+            .into_iter()
+            .partition(|cell| cell.vertices.contains(&u));
+        self.cells = rest;
+        if star.is_empty() || star.iter().any(|cell| cell.normal.is_some()) {
+            self.cells.extend(star);
+            return;
+        }
+
+        let link: Vec<usize> = star
+            .iter()
+            .flat_map(|cell| cell.vertices.iter().cloned())
+            .filter(|v| *v != u)
+            .collect::<BTreeSet<usize>>()
+            .into_iter()
+            .collect();
+        let points: Vec<Vec<f64>> = link.iter().map(|v| self.points[*v].clone()).collect();
+        let weights: Vec<f64> = link.iter().map(|v| self.weights[*v]).collect();
+        // The vertices of the link are not redundant among themselves.
+        let filling: Vec<Cell> = regular_triangulation(&points, &weights, false)
+            .into_iter()
+            .map(|cell| cell.into_iter().map(|i| link[i]).collect::<Vec<usize>>())
+            .filter(|cell| {
+                let centroid = self.centroid(cell);
+                star.iter().any(|s| self.barycentric_min(&s.vertices, &centroid) > 0.0)
+            })
+            .filter_map(|cell| self.make_cell(cell))
+            .collect();
+
+        // The filling must have the same boundary as the star.
+        let boundary = |cells: &[Cell]| {
+            let mut facets = BTreeMap::new();
+            for cell in cells.iter() {
+                for i in 0..cell.vertices.len() {
+                    let mut facet = cell.vertices.clone();
+                    facet.remove(i);
+                    *facets.entry(facet).or_insert(0) += 1;
+                }
+            }
+            facets
+                .into_iter()
+                .filter(|(_, count)| *count == 1)
+                .map(|(facet, _)| facet)
+                .collect::<Vec<_>>()
+        };
+        let redundant = boundary(&filling) == boundary(&star)
+            && filling
+                .iter()
+                .max_by(|a, b| {
+                    let a = self.barycentric_min(&a.vertices, &self.points[u]);
+                    let b = self.barycentric_min(&b.vertices, &self.points[u]);
+                    a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+                })
+                .is_some_and(|cell| !self.conflicts(cell, u));
+
+        if redundant {
+            self.cells.extend(filling);
+        } else {
+            self.cells.extend(star);
+        }
+    }
+
+    fn centroid(&self, cell: &[usize]) -> Vec<f64> {
+        let mut centroid = vec![0.0; self.interior.len()];
+        for v in cell.iter() {
+            for (x, y) in centroid.iter_mut().zip(self.points[*v].iter()) {
+                *x += y / cell.len() as f64;
+            }
+        }
+        centroid
+    }
+
+    /// Returns the least barycentric coordinate of `point` with respect to a finite cell.
+    fn barycentric_min(&self, cell: &[usize], point: &[f64]) -> f64 {
+        let dim = point.len();
+        let matrix = (0..=dim)
+            .map(|i| {
+                cell.iter()
+                    .map(|v| if i < dim { self.points[*v][i] } else { 1.0 })
+                    .collect()
+            })
+            .collect();
+        let rhs = point.iter().cloned().chain(std::iter::once(1.0)).collect();
+        match solve(matrix, rhs) {
+            Some(coords) => coords.into_iter().fold(f64::INFINITY, f64::min),
+            None => f64::NEG_INFINITY,
+        }
+    }
+}
+
+/// Returns the signed offset of `point` from the hyperplane through `origin` with `normal`.
+fn offset(normal: &[f64], origin: &[f64], point: &[f64]) -> f64 {
+    normal
+        .iter()
+        .zip(point.iter().zip(origin.iter()))
+        .map(|(n, (p, o))| n * (p - o))
+        .sum()
+}
+
+/// Computes the determinant of a square matrix.
+///
+/// The matrices up to `2 x 2` are expanded directly, so their determinants are exact
+/// for small integers.
+fn determinant(mut m: Vec<Vec<f64>>) -> f64 {
+    match m.len() {
+        0 => 1.0,
+        1 => m[0][0],
+        2 => m[0][0] * m[1][1] - m[0][1] * m[1][0],
+        n => {
+            let mut det = 1.0;
+            for col in 0..n {
+                let pivot = (col..n)
+                    .max_by(|i, j| m[*i][col].abs().partial_cmp(&m[*j][col].abs()).unwrap())
+                    .unwrap();
+                if m[pivot][col] == 0.0 {
+                    return 0.0;
+                }
+                if pivot != col {
+                    m.swap(col, pivot);
+                    det = -det;
+                }
+                det *= m[col][col];
+                for row in col + 1..n {
+                    let factor = m[row][col] / m[col][col];
+                    let (upper, lower) = m.split_at_mut(row);
+                    for (x, y) in lower[0][col..].iter_mut().zip(upper[col][col..].iter()) {
+                        *x -= factor * y;
+                    }
+                }
+            }
+            det
+        }
+    }
+}
+
+/// Computes the regular triangulation of the weighted points by the Bowyer-Watson algorithm.
+///
+/// The convex hull is handled by a vertex at infinity, so no bounding simplex is used.
+/// It returns the maximal cells as sorted lists of vertices, and the cells span the affine
+/// hull of the points. If `remove_redundant` is set, the neighbors of each new point which
+/// have become redundant are removed after its insertion.
+fn regular_triangulation(
+    points: &[Vec<f64>],
+    weights: &[f64],
+    remove_redundant: bool,
+) -> Vec<Vec<usize>> {
+    let dim = points[0].len();
+    for p in points.iter() {
+        assert_eq!(p.len(), dim);
+    }
+
+    // Choose affinely independent points greedily, each farthest from the span of the
+    // previous ones, together with an orthonormal basis of their span.
+    let extent = points
+        .iter()
+        .map(|p| squared_distance(p, &points[0]))
+        .fold(0.0f64, f64::max)
+        .sqrt();
+    let mut simplex = vec![0];
+    let mut basis: Vec<Vec<f64>> = Vec::new();
+    while basis.len() < dim {
+        let residual = |p: &[f64]| {
+            let mut r: Vec<f64> = p.iter().zip(points[0].iter()).map(|(x, o)| x - o).collect();
+            for b in basis.iter() {
+                let t: f64 = r.iter().zip(b.iter()).map(|(x, y)| x * y).sum();
+                for (x, y) in r.iter_mut().zip(b.iter()) {
+                    *x -= t * y;
+                }
+            }
+            r
+        };
+        let (farthest, r) = points
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (i, residual(p)))
+            .max_by(|(_, a), (_, b)| {
+                let a: f64 = a.iter().map(|x| x * x).sum();
+                let b: f64 = b.iter().map(|x| x * x).sum();
+                a.partial_cmp(&b).unwrap()
+            })
+            .unwrap();
+        let norm = r.iter().map(|x| x * x).sum::<f64>().sqrt();
+        if norm <= 1e-10 * extent {
+            break;
+        }
+        simplex.push(farthest);
+        basis.push(r.into_iter().map(|x| x / norm).collect());
+    }
+
+    if basis.is_empty() {
+        // All the points coincide, and the heaviest one hides the others.
+        let heaviest = (0..points.len()).fold(0, |h, i| if weights[i] > weights[h] { i } else { h });
+        return vec![vec![heaviest]];
+    }
+    if basis.len() < dim {
+        // Triangulate the points in the coordinates of their affine hull.
+        let projected: Vec<Vec<f64>> = points
+            .iter()
+            .map(|p| {
+                basis
+                    .iter()
+                    .map(|b| {
+                        p.iter()
+                            .zip(points[0].iter())
+                            .zip(b.iter())
+                            .map(|((x, o), y)| (x - o) * y)
+                            .sum()
+                    })
+                    .collect()
+            })
+            .collect();
+        return regular_triangulation(&projected, weights, remove_redundant);
+    }
+
+    // The points of the same weight are never redundant.
+    let remove_redundant = remove_redundant && weights.iter().any(|w| *w != weights[0]);
+    let mut triangulation = Triangulation::new(points.to_vec(), weights.to_vec(), &simplex);
+    for p in (0..points.len()).filter(|p| !simplex.contains(p)) {
+        let neighbors = triangulation.insert(p);
+        if remove_redundant {
+            for u in neighbors.into_iter() {
+                triangulation.remove_if_redundant(u);
+            }
+        }
+    }
+
+    let mut cells: Vec<Vec<usize>> = triangulation
+        .cells
+        .into_iter()
+        .filter(|cell| cell.normal.is_none())
+        .map(|cell| cell.vertices)
+        .collect();
+    cells.sort();
+    cells
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::alpha::{alpha_filtration, weighted_alpha_filtration, Triangulation};
+    use crate::traits::*;
+    use crate::Persistence;
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-9, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn test_alpha_square() {
+        let points = vec![
+            vec![0.0, 0.0],
+            vec![1.0, 0.0],
+            vec![0.0, 1.0],
+            vec![1.0, 1.0],
+        ];
+        let filt = alpha_filtration(&points);
+        assert_eq!(filt.len(), 11);
+
+        let diagram = filt
+            .diagram()
+            .unwrap()
+            .into_iter()
+            .filter(|(_, Persistence(birth, death))| Some(*birth) != *death)
+            .collect::<Vec<_>>();
+        assert_eq!(diagram.len(), 5);
+        assert_eq!(diagram.iter().filter(|(dim, _)| *dim == 0).count(), 4);
+        let (_, Persistence(birth, death)) = diagram.iter().find(|(dim, _)| *dim == 1).unwrap();
+        assert_close(*birth, 0.25);
+        assert_close(death.unwrap(), 0.5);
+    }
+
+    #[test]
+    fn test_alpha_obtuse_triangle() {
+        let points = vec![vec![0.0, 0.0], vec![4.0, 0.0], vec![2.0, 1.0]];
+        let filt = alpha_filtration(&points);
+        assert_eq!(filt.len(), 7);
+
+        // The long edge is attached to the triangle.
+        let (index, _) = filt
+            .complex
+            .basis
+            .iter()
+            .find(|(_, s)| s.vertices().cloned().collect::<Vec<_>>() == vec![0, 1])
+            .unwrap();
+        assert_close(filt.values[index], 6.25);
+        assert_close(filt.values[6], 6.25);
+        assert_eq!(filt.complex.basis[6].dimension(), 2);
+    }
+
+    #[test]
+    fn test_weighted_alpha() {
+        let points = vec![vec![0.0, 0.0], vec![2.0, 0.0]];
+        let filt = weighted_alpha_filtration(&points, &[1.0, 1.0]);
+        assert_eq!(filt.len(), 3);
+        assert_close(filt.values[0], -1.0);
+        assert_close(filt.values[1], -1.0);
+        assert_close(filt.values[2], 0.0);
+    }
+
+    #[test]
+    fn test_alpha_hull_completeness() {
+        // The thin triangle on the convex hull has a circumcircle far larger than the points.
+        let points = vec![
+            vec![0.0, 0.0],
+            vec![10.0, 0.0],
+            vec![5.0, 1e-4],
+            vec![5.0, 5.0],
+        ];
+        let filt = alpha_filtration(&points);
+        assert_eq!(filt.len(), 4 + 6 + 3);
+        let thin = filt
+            .complex
+            .basis
+            .iter()
+            .find(|(_, s)| s.vertices().cloned().collect::<Vec<_>>() == vec![0, 1, 2]);
+        assert!(thin.is_some());
+    }
+
+    #[test]
+    fn test_weighted_alpha_hidden_point() {
+        // The point 3 is hidden by the heavy point 4 inserted after it.
+        let points = vec![
+            vec![0.0, 0.0],
+            vec![4.0, 0.0],
+            vec![2.0, 4.0],
+            vec![2.0, 1.0],
+            vec![2.0, 1.5],
+        ];
+        let filt = weighted_alpha_filtration(&points, &[0.0, 0.0, 0.0, 0.0, 4.0]);
+        assert_eq!(filt.len(), 4 + 6 + 3);
+        assert!(filt
+            .complex
+            .basis
+            .iter()
+            .all(|(_, s)| s.vertices().all(|v| *v != 3)));
+    }
+
+    #[test]
+    fn test_remove_redundant_vertex() {
+        let points = vec![
+            vec![0.0, 0.0],
+            vec![4.0, 0.0],
+            vec![2.0, 4.0],
+            vec![2.0, 1.0],
+            vec![2.0, 1.5],
+        ];
+        let mut triangulation = Triangulation::new(points, vec![0.0; 5], &[0, 1, 2]);
+        triangulation.insert(3);
+        triangulation.insert(4);
+        triangulation.remove_if_redundant(3);
+        assert!(triangulation.cells.iter().any(|c| c.vertices.contains(&3)));
+
+        // The point 3 becomes redundant when the weight of the point 4 grows.
+        triangulation.weights[4] = 4.0;
+        let cells = std::mem::take(&mut triangulation.cells);
+        for cell in cells.into_iter() {
+            let cell = triangulation.make_cell(cell.vertices).unwrap();
+            triangulation.cells.push(cell);
+        }
+        triangulation.remove_if_redundant(3);
+        let mut cells = triangulation
+            .cells
+            .iter()
+            .filter(|c| c.normal.is_none())
+            .map(|c| c.vertices.clone())
+            .collect::<Vec<_>>();
+        cells.sort();
+        assert_eq!(cells, vec![vec![0, 1, 4], vec![0, 2, 4], vec![1, 2, 4]]);
+    }
+}
@@ -0,0 +1,553 @@
+use crate::complex::{self, Complex, ComplexError};
+use crate::filtration::Filtration;
+use crate::indexed_vec::IndexedVec;
+use crate::pair::Pair;
+use crate::sign::Sign;
+use crate::simplex::Simplex;
+use crate::traits::*;
+use crate::z2reduce::Z2ColumnReduce;
+use crate::z2vector::Z2VectorVec;
+use crate::Persistence;
+use failure::Fail;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Fail)]
+pub enum BifiltrationError {
+    #[fail(display = "{}", _0)]
+    Complex(#[cause] ComplexError),
+    #[fail(display = "no grade is given")]
+    NoGrade,
+    #[fail(display = "grades are comparable")]
+    ComparableGrades,
+    #[fail(display = "element appears before its face")]
+    AppearsBeforeFace,
+    #[fail(display = "grade {} is not less than or equal to {}", _0, _1)]
+    NotLessOrEqual(Bigrade, Bigrade),
+    #[fail(display = "direction {} is not positive", _0)]
+    InvalidDirection(Bigrade),
+    #[fail(display = "vertex {} has no function value", _0)]
+    MissingValue(usize),
+}
+
+impl From<ComplexError> for BifiltrationError {
+    fn from(e: ComplexError) -> Self {
+        BifiltrationError::Complex(e)
+    }
+}
+
+/// A point of the parameter space R^2
+///
+/// Bigrades are ordered by the product order, so two bigrades may be incomparable.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bigrade(pub f64, pub f64);
+
+impl Bigrade {
+    /// Returns the least upper bound of `self` and `other`.
+    pub fn join(&self, other: &Bigrade) -> Bigrade {
+        Bigrade(self.0.max(other.0), self.1.max(other.1))
+    }
+}
+
+impl PartialOrd for Bigrade {
+    fn partial_cmp(&self, other: &Bigrade) -> Option<Ordering> {
+        match (self.0.partial_cmp(&other.0)?, self.1.partial_cmp(&other.1)?) {
+            (Ordering::Equal, y) => Some(y),
+            (x, Ordering::Equal) => Some(x),
+            (x, y) if x == y => Some(x),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Bigrade {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "({}, {})", self.0, self.1)
+    }
+}
+
+/// A line of positive slope in the parameter space
+///
+/// The line consists of the points `point + t * direction` for real `t`.
+#[derive(Debug, Clone, Copy)]
+pub struct Line {
+    point: Bigrade,
+    direction: Bigrade,
+}
+
+impl Line {
+    /// Creates a new line through `point` with `direction`.
+    ///
+    /// The components of `direction` must be non-negative and not both zero.
+    pub fn new(point: Bigrade, direction: Bigrade) -> Result<Line, BifiltrationError> {
+        let non_negative = direction.0 >= 0.0 && direction.1 >= 0.0;
+        if !non_negative || (direction.0 == 0.0 && direction.1 == 0.0) {
+            return Err(BifiltrationError::InvalidDirection(direction));
+        }
+        Ok(Line {
+            point: point,
+            direction: direction,
+        })
+    }
+
+    /// Returns the point of the line at the parameter `t`.
+    pub fn at(&self, t: f64) -> Bigrade {
+        Bigrade(
+            self.point.0 + t * self.direction.0,
+            self.point.1 + t * self.direction.1,
+        )
+    }
+
+    /// Returns the least parameter `t` such that `grade <= self.at(t)`.
+    ///
+    /// It returns the positive infinity if the line never gets above `grade`.
+    pub fn entry(&self, grade: &Bigrade) -> f64 {
+        let coord = |g: f64, p: f64, d: f64| {
+            if d > 0.0 {
+                (g - p) / d
+            } else if g <= p {
+                f64::NEG_INFINITY
+            } else {
+                f64::INFINITY
+            }
+        };
+        coord(grade.0, self.point.0, self.direction.0).max(coord(
+            grade.1,
+            self.point.1,
+            self.direction.1,
+        ))
+    }
+}
+
+/// Bifiltered complex
+///
+/// Each basis element has a set of mutually incomparable grades.
+/// The element belongs to the complex at `p` if one of its grades is less than or equal to `p`.
+/// The basis must be ordered so that faces come before their cofaces.
+#[derive(Debug, Clone)]
+pub struct BifilteredComplex<G> {
+    pub complex: Complex<IndexedVec<G>, G>,
+    pub grades: Vec<Vec<Bigrade>>,
+}
+
+impl<G> BifilteredComplex<G>
+where
+    G: 'static + Clone + PartialEq + ChainGenerator + for<'a> ChainGeneratorBoundary<'a, G>,
+{
+    pub fn new() -> Self {
+        BifilteredComplex {
+            complex: Complex::new(),
+            grades: Vec::new(),
+        }
+    }
+
+    /// Pushes an element with its grades.
+    ///
+    /// It checks that the grades are mutually incomparable and
+    /// that every face of `elem` appears at or before each of the grades.
+    pub fn push(&mut self, elem: G, grades: Vec<Bigrade>) -> Result<(), BifiltrationError> {
+        if grades.is_empty() {
+            return Err(BifiltrationError::NoGrade);
+        }
+        for (i, a) in grades.iter().enumerate() {
+            if grades[i + 1..].iter().any(|b| a.partial_cmp(b).is_some()) {
+                return Err(BifiltrationError::ComparableGrades);
+            }
+        }
+
+        let faces: Vec<(usize, Sign)> = complex::compute_boundary(self.complex.basis.iter(), &elem)
+            .ok_or(BifiltrationError::Complex(ComplexError::ComplexIsNotFiltered))?;
+        for (face, _) in faces.iter() {
+            if !grades.iter().all(|g| self.contains_at(*face, g)) {
+                return Err(BifiltrationError::AppearsBeforeFace);
+            }
+        }
+
+        self.complex.push(elem)?;
+        self.grades.push(grades);
+        Ok(())
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.grades.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.grades.is_empty()
+    }
+
+    /// Checks whether the element at `index` belongs to the complex at `point`.
+    pub fn contains_at(&self, index: usize, point: &Bigrade) -> bool {
+        self.grades[index].iter().any(|g| g <= point)
+    }
+
+    /// Computes the rank of the map from the `dim`-th homology at `p` to that at `q`.
+    ///
+    /// The grade `p` must be less than or equal to `q`.
+    pub fn rank(&self, dim: usize, p: &Bigrade, q: &Bigrade) -> Result<usize, failure::Error> {
+        Ok(self.chain_ranks(dim, p, &[*q])?[0])
+    }
+
+    /// Computes the ranks of the maps from the `dim`-th homology at `p` to those at the grades
+    /// of `chain`.
+    ///
+    /// The grades of `chain` must be non-decreasing and not less than `p`. The complexes at `p`
+    /// and at the grades of `chain` form a one-parameter filtration, so a single reduction gives
+    /// all the ranks: the rank at `chain[i]` is the number of the classes born at `p` which
+    /// are still alive at `chain[i]`.
+    fn chain_ranks(
+        &self,
+        dim: usize,
+        p: &Bigrade,
+        chain: &[Bigrade],
+    ) -> Result<Vec<usize>, failure::Error> {
+        let mut prev = p;
+        for q in chain.iter() {
+            match prev.partial_cmp(q) {
+                Some(Ordering::Less) | Some(Ordering::Equal) => prev = q,
+                _ => return Err(BifiltrationError::NotLessOrEqual(*prev, *q).into()),
+            }
+        }
+
+        // The stage of an element is 0 if it belongs to K_p and i + 1 if it first appears
+        // at chain[i]. Faces have stages not larger than those of their cofaces.
+        let mut stages = Vec::new();
+        for (index, gen) in self.complex.basis.iter() {
+            let stage = if self.contains_at(index, p) {
+                Some(0)
+            } else {
+                chain
+                    .iter()
+                    .position(|q| self.contains_at(index, q))
+                    .map(|i| i + 1)
+            };
+            if let Some(stage) = stage {
+                stages.push((stage, gen.clone()));
+            }
+        }
+        stages.sort_by_key(|(stage, _)| *stage);
+
+        let mut comp = Complex::<IndexedVec<G>, G>::new();
+        for (_, gen) in stages.iter() {
+            comp.push_unchecked(gen.clone());
+        }
+
+        let reduce = Z2ColumnReduce::<Z2VectorVec>::from_complex(&comp)?;
+        let mut ranks = vec![0; chain.len()];
+        for (pers, _) in Pair::new(&reduce, reduce.cycles()) {
+            if stages[pers.0].0 != 0 || comp.basis[pers.0].dimension() != dim {
+                continue;
+            }
+            // The class is alive at chain[i] if it dies after the stage i + 1.
+            let death = pers.1.map_or(chain.len() + 1, |d| stages[d].0);
+            for (i, rank) in ranks.iter_mut().enumerate() {
+                if death > i + 1 {
+                    *rank += 1;
+                }
+            }
+        }
+
+        Ok(ranks)
+    }
+
+    /// Computes the Hilbert function on the grid `xs` times `ys`.
+    ///
+    /// The element `[i][j]` of the result is the dimension of
+    /// the `dim`-th homology at `(xs[i], ys[j])`.
+    pub fn hilbert_function(
+        &self,
+        dim: usize,
+        xs: &[f64],
+        ys: &[f64],
+    ) -> Result<Vec<Vec<usize>>, failure::Error> {
+        let mut values = Vec::with_capacity(xs.len());
+        for x in xs.iter() {
+            let mut row = Vec::with_capacity(ys.len());
+            for y in ys.iter() {
+                let p = Bigrade(*x, *y);
+                row.push(self.rank(dim, &p, &p)?);
+            }
+            values.push(row);
+        }
+        Ok(values)
+    }
+
+    /// Computes the rank invariant on the grid `xs` times `ys`.
+    pub fn rank_invariant(
+        &self,
+        dim: usize,
+        xs: &[f64],
+        ys: &[f64],
+    ) -> Result<RankInvariant, failure::Error> {
+        let mut ranks = BTreeMap::new();
+        for (i, x0) in xs.iter().enumerate() {
+            for (j, y0) in ys.iter().enumerate() {
+                let p = Bigrade(*x0, *y0);
+                // The grid points above p on each vertical line form a chain.
+                let mut above: Vec<usize> = (0..ys.len()).filter(|l| ys[*l] >= *y0).collect();
+                above.sort_by(|a, b| ys[*a].partial_cmp(&ys[*b]).unwrap());
+                for (k, x1) in xs.iter().enumerate().filter(|(_, x1)| *x1 >= x0) {
+                    let chain: Vec<Bigrade> = above.iter().map(|l| Bigrade(*x1, ys[*l])).collect();
+                    let chain_ranks = self.chain_ranks(dim, &p, &chain)?;
+                    for (l, rank) in above.iter().zip(chain_ranks) {
+                        ranks.insert(((i, j), (k, *l)), rank);
+                    }
+                }
+            }
+        }
+        Ok(RankInvariant {
+            xs: xs.to_vec(),
+            ys: ys.to_vec(),
+            ranks: ranks,
+        })
+    }
+
+    /// Restricts the bifiltration to `line` and returns the one-parameter filtration.
+    ///
+    /// The filtration value of an element is the least parameter of the line
+    /// at which the element appears. The elements which never appear on the line are dropped.
+    pub fn restrict_to_line(&self, line: &Line) -> Filtration<G, f64> {
+        let elems = self
+            .complex
+            .basis
+            .iter()
+            .map(|(index, gen)| {
+                let entry = self.grades[index]
+                    .iter()
+                    .map(|g| line.entry(g))
+                    .fold(f64::INFINITY, f64::min);
+                (gen.clone(), entry)
+            })
+            .filter(|(_, entry)| entry.is_finite())
+            .collect();
+        Filtration::from_unsorted(elems)
+    }
+
+    /// Computes the fibered barcode of the `dim`-th homology along `line`.
+    ///
+    /// The birth and death values are the parameters of the line.
+    /// The bars of length zero are removed.
+    pub fn fibered_barcode(
+        &self,
+        dim: usize,
+        line: &Line,
+    ) -> Result<Vec<Persistence<f64>>, failure::Error> {
+        let barcode = self
+            .restrict_to_line(line)
+            .diagram()?
+            .into_iter()
+            .filter(|(d, _)| *d == dim)
+            .map(|(_, pers)| pers)
+            .filter(|Persistence(birth, death)| Some(*birth) != *death)
+            .collect();
+        Ok(barcode)
+    }
+}
+
+impl BifilteredComplex<Simplex> {
+    /// Creates the function bifiltration of a filtered simplicial complex.
+    ///
+    /// The grade of a simplex is the pair of the maximum of `function` over its vertices
+    /// and its filtration value. Using a codensity function as `function` gives
+    /// a density-plus-scale bifiltration. Every vertex must have a value in `function`.
+    pub fn from_vertex_function(
+        filt: &Filtration<Simplex, f64>,
+        function: &[f64],
+    ) -> Result<Self, BifiltrationError> {
+        let mut bifilt = BifilteredComplex::new();
+        for (index, simplex) in filt.complex.basis.iter() {
+            let mut value = f64::NEG_INFINITY;
+            for v in simplex.vertices() {
+                let f = function.get(*v).ok_or(BifiltrationError::MissingValue(*v))?;
+                value = value.max(*f);
+            }
+            bifilt.complex.push_unchecked(simplex.clone());
+            bifilt.grades.push(vec![Bigrade(value, filt.values[index])]);
+        }
+        Ok(bifilt)
+    }
+}
+
+impl<G> Default for BifilteredComplex<G>
+where
+    G: 'static + Clone + PartialEq + ChainGenerator + for<'a> ChainGeneratorBoundary<'a, G>,
+{
+    fn default() -> Self {
+        BifilteredComplex::new()
+    }
+}
+
+/// A grid point indexed by a pair of indices into `xs` and `ys`
+pub type GridPoint = (usize, usize);
+
+/// Rank invariant on a grid
+#[derive(Debug, Clone)]
+pub struct RankInvariant {
+    xs: Vec<f64>,
+    ys: Vec<f64>,
+    ranks: BTreeMap<(GridPoint, GridPoint), usize>,
+}
+
+impl RankInvariant {
+    pub fn xs(&self) -> &[f64] {
+        &self.xs
+    }
+
+    pub fn ys(&self) -> &[f64] {
+        &self.ys
+    }
+
+    /// Returns the rank of the map from the grid point `p` to `q`.
+    ///
+    /// It returns `None` if `p` is not less than or equal to `q`.
+    pub fn get(&self, p: GridPoint, q: GridPoint) -> Option<usize> {
+        self.ranks.get(&(p, q)).cloned()
+    }
+
+    /// Returns an iterator over the pairs of grid points and the ranks.
+    pub fn iter<'a>(&'a self) -> impl Iterator<Item = (GridPoint, GridPoint, usize)> + 'a {
+        self.ranks.iter().map(|((p, q), rank)| (*p, *q, *rank))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bifiltration::{Bigrade, BifilteredComplex, BifiltrationError, Line};
+    use crate::filtration::Filtration;
+    use crate::simplex;
+    use crate::simplex::Simplex;
+    use crate::testing::triangle_boundary;
+    use crate::traits::*;
+    use crate::Persistence;
+
+    // A circle which appears at (1, 0) and is filled at (1, 2) and at (3, 1).
+    fn circle() -> BifilteredComplex<Simplex> {
+        let mut bifilt = BifilteredComplex::new();
+        for (_, simplex) in triangle_boundary().basis.iter() {
+            let x = if *simplex == simplex![0, 2] { 1.0 } else { 0.0 };
+            bifilt.push(simplex.clone(), vec![Bigrade(x, 0.0)]).unwrap();
+        }
+        bifilt
+            .push(
+                simplex![0, 1, 2],
+                vec![Bigrade(1.0, 2.0), Bigrade(3.0, 1.0)],
+            )
+            .unwrap();
+        bifilt
+    }
+
+    #[test]
+    fn test_bigrade_order() {
+        assert!(Bigrade(0.0, 0.0) <= Bigrade(1.0, 0.0));
+        assert!(Bigrade(0.0, 1.0) < Bigrade(1.0, 1.0));
+        assert_eq!(Bigrade(0.0, 1.0).partial_cmp(&Bigrade(1.0, 0.0)), None);
+    }
+
+    #[test]
+    fn test_bifiltration_push() {
+        let mut bifilt = circle();
+        assert!(bifilt
+            .push(simplex![3], vec![Bigrade(0.0, 1.0), Bigrade(1.0, 1.0)])
+            .is_err());
+        assert!(bifilt
+            .push(simplex![0, 3], vec![Bigrade(0.0, 0.0)])
+            .is_err());
+        bifilt.push(simplex![3], vec![Bigrade(1.0, 1.0)]).unwrap();
+        assert!(bifilt
+            .push(simplex![0, 3], vec![Bigrade(0.0, 0.0)])
+            .is_err());
+    }
+
+    #[test]
+    fn test_hilbert_function() {
+        let bifilt = circle();
+        let xs = [0.0, 1.0, 2.0, 3.0];
+        let ys = [0.0, 1.0, 2.0];
+
+        let h0 = bifilt.hilbert_function(0, &xs, &ys).unwrap();
+        assert!(h0.iter().all(|row| row.iter().all(|v| *v == 1)));
+
+        let h1 = bifilt.hilbert_function(1, &xs, &ys).unwrap();
+        assert_eq!(
+            h1,
+            vec![vec![0, 0, 0], vec![1, 1, 0], vec![1, 1, 0], vec![1, 0, 0]]
+        );
+
+        let rank = bifilt.rank_invariant(1, &xs, &ys).unwrap();
+        assert_eq!(rank.get((1, 0), (2, 1)), Some(1));
+        assert_eq!(rank.get((1, 0), (3, 1)), Some(0));
+        assert_eq!(rank.get((1, 1), (1, 0)), None);
+    }
+
+    #[test]
+    fn test_rank_invariant() {
+        let bifilt = circle();
+        let xs = [3.0, 0.0, 2.0, 1.0];
+        let ys = [1.0, 2.0, 0.0];
+
+        for dim in 0..2 {
+            let rank = bifilt.rank_invariant(dim, &xs, &ys).unwrap();
+            for (i, x0) in xs.iter().enumerate() {
+                for (j, y0) in ys.iter().enumerate() {
+                    for (k, x1) in xs.iter().enumerate() {
+                        for (l, y1) in ys.iter().enumerate() {
+                            let p = Bigrade(*x0, *y0);
+                            let q = Bigrade(*x1, *y1);
+                            let expected = if p <= q {
+                                Some(bifilt.rank(dim, &p, &q).unwrap())
+                            } else {
+                                None
+                            };
+                            assert_eq!(rank.get((i, j), (k, l)), expected);
+                        }
+                    }
+                }
+            }
+        }
+
+        assert!(bifilt
+            .rank(1, &Bigrade(1.0, 0.0), &Bigrade(0.0, 1.0))
+            .is_err());
+    }
+
+    #[test]
+    fn test_fibered_barcode() {
+        let bifilt = circle();
+        let line = Line::new(Bigrade(0.0, 0.0), Bigrade(1.0, 1.0)).unwrap();
+        assert_eq!(
+            bifilt.fibered_barcode(1, &line).unwrap(),
+            vec![Persistence(1.0, Some(2.0))]
+        );
+        assert_eq!(
+            bifilt.fibered_barcode(0, &line).unwrap(),
+            vec![Persistence(0.0, None)]
+        );
+
+        let line = Line::new(Bigrade(0.0, 0.0), Bigrade(1.0, 0.0)).unwrap();
+        assert_eq!(
+            bifilt.fibered_barcode(1, &line).unwrap(),
+            vec![Persistence(1.0, None)]
+        );
+
+        assert!(Line::new(Bigrade(0.0, 0.0), Bigrade(0.0, 0.0)).is_err());
+        assert!(Line::new(Bigrade(0.0, 0.0), Bigrade(-1.0, 1.0)).is_err());
+    }
+
+    #[test]
+    fn test_from_vertex_function() {
+        let filt = Filtration::from_unsorted(vec![
+            (simplex![0], 0.0),
+            (simplex![1], 0.0),
+            (simplex![0, 1], 1.0),
+        ]);
+        let bifilt = BifilteredComplex::from_vertex_function(&filt, &[2.0, 1.0]).unwrap();
+        assert_eq!(bifilt.grades[2], vec![Bigrade(2.0, 1.0)]);
+
+        match BifilteredComplex::from_vertex_function(&filt, &[2.0]) {
+            Err(BifiltrationError::MissingValue(1)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
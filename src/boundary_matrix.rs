@@ -0,0 +1,133 @@
+use crate::complex::Complex;
+use crate::pair::Pair;
+use crate::sign::Sign;
+use crate::traits::*;
+use crate::z2reduce::Z2ColumnReduce;
+use crate::z2vector::Z2VectorVec;
+use crate::Persistence;
+
+/// Boundary matrix over Z2
+///
+/// The `i`-th column is the boundary of the `i`-th basis element, given by the row indices
+/// of its nonzero entries in ascending order, together with the dimension of the element.
+/// The row indices of a column must be less than its index.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BoundaryMatrix {
+    pub dimensions: Vec<usize>,
+    pub columns: Vec<Vec<usize>>,
+}
+
+impl BoundaryMatrix {
+    pub fn new() -> Self {
+        BoundaryMatrix {
+            dimensions: Vec::new(),
+            columns: Vec::new(),
+        }
+    }
+
+    /// Creates the boundary matrix of the complex.
+    ///
+    /// The rows and the columns are the positions from the start of the basis.
+    pub fn from_complex<V, G>(complex: &Complex<V, G>) -> Result<Self, failure::Error>
+    where
+        G: PartialEq + ChainGenerator + for<'a> ChainGeneratorBoundary<'a, G>,
+        V: IndexedSet<G> + for<'a> IndexedSetIters<'a, G>,
+    {
+        let start = complex.basis.index_start();
+        let mut matrix = BoundaryMatrix::new();
+        for (result, (_, gen)) in complex
+            .boundaries::<Vec<(usize, Sign)>>()
+            .zip(complex.basis.iter())
+        {
+            let (_, boundary) = result?;
+            let column = boundary.into_iter().map(|(i, _)| i - start).collect();
+            matrix.push(gen.dimension(), column);
+        }
+        Ok(matrix)
+    }
+
+    /// Appends a column with the dimension.
+    ///
+    /// The row indices are sorted and the duplicated ones are cancelled out.
+    pub fn push(&mut self, dimension: usize, mut column: Vec<usize>) {
+        column.sort();
+        let mut reduced: Vec<usize> = Vec::with_capacity(column.len());
+        for i in column.into_iter() {
+            if reduced.last() == Some(&i) {
+                reduced.pop();
+            } else {
+                reduced.push(i);
+            }
+        }
+        if let Some(i) = reduced.last() {
+            assert!(*i < self.columns.len());
+        }
+        self.dimensions.push(dimension);
+        self.columns.push(reduced);
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.columns.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.columns.is_empty()
+    }
+
+    /// Reduces the matrix by the column reduction.
+    pub fn reduce(&self) -> Z2ColumnReduce<Z2VectorVec> {
+        let mut reduce = Z2ColumnReduce::new(0);
+        for column in self.columns.iter() {
+            reduce.push(Z2VectorVec::from(column.clone()));
+        }
+        reduce
+    }
+
+    /// Computes the persistence pairs of the column indices with their dimensions.
+    pub fn pairs(&self) -> Vec<(usize, Persistence<usize>)> {
+        let reduce = self.reduce();
+        Pair::new(&reduce, reduce.cycles())
+            .map(|(pers, _)| (self.dimensions[pers.0], pers))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::boundary_matrix::BoundaryMatrix;
+    use crate::complex::Complex;
+    use crate::indexed_vec::IndexedVec;
+    use crate::simplex;
+    use crate::simplex::Simplex;
+    use crate::Persistence;
+
+    #[test]
+    fn test_boundary_matrix() {
+        let mut complex = Complex::<IndexedVec<Simplex>, Simplex>::new();
+        complex.push(simplex![0]).unwrap();
+        complex.push(simplex![1]).unwrap();
+        complex.push(simplex![2]).unwrap();
+        complex.push(simplex![0, 1]).unwrap();
+        complex.push(simplex![1, 2]).unwrap();
+        complex.push(simplex![0, 2]).unwrap();
+        complex.push(simplex![0, 1, 2]).unwrap();
+
+        let matrix = BoundaryMatrix::from_complex(&complex).unwrap();
+        assert_eq!(matrix.dimensions, vec![0, 0, 0, 1, 1, 1, 2]);
+        assert_eq!(matrix.columns[3], vec![0, 1]);
+        assert_eq!(matrix.columns[6], vec![3, 4, 5]);
+
+        let pairs = matrix.pairs();
+        assert_eq!(
+            pairs,
+            vec![
+                (0, Persistence(0, None)),
+                (0, Persistence(1, Some(3))),
+                (0, Persistence(2, Some(4))),
+                (1, Persistence(5, Some(6))),
+            ]
+        );
+    }
+}
@@ -0,0 +1,107 @@
+use crate::filtration::Filtration;
+use crate::geometry::{minimal_enclosing_ball, squared_distance};
+use crate::simplex::Simplex;
+
+/// Builds the Čech filtration of the points.
+///
+/// The filtration value of a simplex is the radius of the minimal enclosing ball of its vertices,
+/// i.e. the simplex enters the Čech complex when the balls of that radius centered at
+/// the vertices have a common point. The simplices of dimension greater than `max_dim` or
+/// with the value greater than `max_radius` are not included.
+/// The vertices of the complex are the indices of `points`.
+pub fn cech_filtration(
+    points: &[Vec<f64>],
+    max_dim: usize,
+    max_radius: f64,
+) -> Filtration<Simplex, f64> {
+    let n = points.len();
+    let max_distance2 = 4.0 * max_radius * max_radius;
+
+    // The vertices which may span an edge, with larger indices.
+    let neighbors: Vec<Vec<usize>> = (0..n)
+        .map(|u| {
+            (u + 1..n)
+                .filter(|v| squared_distance(&points[u], &points[*v]) <= max_distance2)
+                .collect()
+        })
+        .collect();
+
+    let mut elems = Vec::new();
+    for v in 0..n {
+        elems.push((Simplex::new(vec![v]), 0.0));
+        if max_dim > 0 {
+            expand(points, &neighbors, vec![v], &neighbors[v], max_dim, max_radius, &mut elems);
+        }
+    }
+
+    Filtration::from_unsorted(elems)
+}
+
+/// Adds the cofaces of `simplex` obtained by adding the vertices in `candidates`.
+///
+/// The radius of the minimal enclosing ball is monotone, so the cofaces of
+/// a simplex with too large radius are not searched.
+fn expand(
+    points: &[Vec<f64>],
+    neighbors: &[Vec<usize>],
+    simplex: Vec<usize>,
+    candidates: &[usize],
+    max_dim: usize,
+    max_radius: f64,
+    elems: &mut Vec<(Simplex, f64)>,
+) {
+    for (i, v) in candidates.iter().enumerate() {
+        let mut coface = simplex.clone();
+        coface.push(*v);
+
+        let coords: Vec<&[f64]> = coface.iter().map(|u| points[*u].as_slice()).collect();
+        let (_, radius2) = minimal_enclosing_ball(&coords);
+        let radius = radius2.max(0.0).sqrt();
+        if radius > max_radius {
+            continue;
+        }
+        elems.push((Simplex::new(coface.clone()), radius));
+
+        if coface.len() <= max_dim {
+            let next: Vec<usize> = candidates[i + 1..]
+                .iter()
+                .filter(|u| neighbors[*v].binary_search(u).is_ok())
+                .cloned()
+                .collect();
+            expand(points, neighbors, coface, &next, max_dim, max_radius, elems);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cech::cech_filtration;
+    use crate::Persistence;
+
+    #[test]
+    fn test_cech_triangle() {
+        let points = vec![
+            vec![0.0, 0.0],
+            vec![1.0, 0.0],
+            vec![0.5, 0.75f64.sqrt()],
+        ];
+        let filt = cech_filtration(&points, 2, 1.0);
+        assert_eq!(filt.len(), 7);
+
+        let diagram = filt
+            .diagram()
+            .unwrap()
+            .into_iter()
+            .filter(|(dim, _)| *dim == 1)
+            .collect::<Vec<_>>();
+        assert_eq!(diagram.len(), 1);
+        let Persistence(birth, death) = diagram[0].1;
+        assert!((birth - 0.5).abs() < 1e-9);
+        assert!((death.unwrap() - 1.0 / 3.0f64.sqrt()).abs() < 1e-9);
+
+        let filt = cech_filtration(&points, 2, 0.55);
+        assert_eq!(filt.len(), 6);
+        let filt = cech_filtration(&points, 1, 1.0);
+        assert_eq!(filt.len(), 6);
+    }
+}
@@ -8,11 +8,15 @@ pub enum ComplexError {
     ComplexIsNotFiltered,
     #[fail(display = "element already exists")]
     ElementAlreadyExists,
+    #[fail(display = "simplex has too many vertices ({})", _0)]
+    TooManyVertices(usize),
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Complex<V, G> {
     pub basis: V,
+    #[cfg_attr(feature = "serde", serde(skip))]
     _phantom: std::marker::PhantomData<fn() -> G>,
 }
 
@@ -81,6 +85,82 @@ where
             _phantom2: std::marker::PhantomData,
         }
     }
+
+    /// Returns the subcomplex of the elements of dimension at most `k`.
+    pub fn skeleton(&self, k: usize) -> Self
+    where
+        G: Clone,
+        V: for<'a> IndexedSetIters<'a, G>,
+    {
+        self.subcomplex(|gen| gen.dimension() <= k)
+    }
+
+    /// Returns an iterator over the positions and the elements that have `elem` as a face.
+    ///
+    /// Since every element is a face of itself, `elem` is produced if it is in the complex.
+    pub fn cofaces<'a>(&'a self, elem: &'a G) -> impl Iterator<Item = (usize, &'a G)> + 'a
+    where
+        V: IndexedSetIters<'a, G>,
+    {
+        self.basis.iter().filter(move |(_, gen)| elem.is_face_of(gen))
+    }
+
+    /// Returns the closed star of `elem`, i.e. the faces of the elements that have `elem`
+    /// as a face.
+    pub fn star(&self, elem: &G) -> Self
+    where
+        G: Clone,
+        V: for<'a> IndexedSetIters<'a, G>,
+    {
+        let cofaces: Vec<&G> = self.cofaces(elem).map(|(_, gen)| gen).collect();
+        self.subcomplex(|gen| cofaces.iter().any(|coface| gen.is_face_of(coface)))
+    }
+
+    /// Returns the link of `elem`, i.e. the elements of the closed star of `elem` that have
+    /// no common face with `elem`.
+    pub fn link(&self, elem: &G) -> Self
+    where
+        G: Clone,
+        V: for<'a> IndexedSetIters<'a, G>,
+    {
+        let faces: Vec<&G> = self
+            .basis
+            .iter()
+            .filter(|(_, gen)| gen.is_face_of(elem))
+            .map(|(_, gen)| gen)
+            .collect();
+        let cofaces: Vec<&G> = self.cofaces(elem).map(|(_, gen)| gen).collect();
+        self.subcomplex(|gen| {
+            cofaces.iter().any(|coface| gen.is_face_of(coface))
+                && !faces.iter().any(|face| face.is_face_of(gen))
+        })
+    }
+
+    /// Returns the closure of `elems`, i.e. the elements of the complex that are faces of
+    /// some element of `elems`.
+    pub fn closure(&self, elems: &[G]) -> Self
+    where
+        G: Clone,
+        V: for<'a> IndexedSetIters<'a, G>,
+    {
+        self.subcomplex(|gen| elems.iter().any(|elem| gen.is_face_of(elem)))
+    }
+
+    /// Returns the complex of the elements satisfying the predicate in the same order.
+    fn subcomplex<F>(&self, predicate: F) -> Self
+    where
+        G: Clone,
+        V: for<'a> IndexedSetIters<'a, G>,
+        F: Fn(&G) -> bool,
+    {
+        let mut complex = Self::new();
+        for (_, gen) in self.basis.iter() {
+            if predicate(gen) {
+                complex.push_unchecked(gen.clone());
+            }
+        }
+        complex
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -189,3 +269,100 @@ where
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::complex::Complex;
+    use crate::indexed_vec::IndexedVec;
+    use crate::simplex;
+    use crate::simplex::Simplex;
+    use crate::traits::*;
+
+    fn simplices(complex: &Complex<IndexedVec<Simplex>, Simplex>) -> Vec<Simplex> {
+        complex.basis.iter().map(|(_, s)| s.clone()).collect()
+    }
+
+    #[test]
+    fn test_queries() {
+        // Two triangles sharing the edge |1 2| and a dangling edge |3 4|.
+        let mut complex = Complex::<IndexedVec<Simplex>, Simplex>::new();
+        for s in vec![
+            simplex![0],
+            simplex![1],
+            simplex![2],
+            simplex![3],
+            simplex![4],
+            simplex![0, 1],
+            simplex![0, 2],
+            simplex![1, 2],
+            simplex![1, 3],
+            simplex![2, 3],
+            simplex![3, 4],
+            simplex![0, 1, 2],
+            simplex![1, 2, 3],
+        ] {
+            complex.push(s).unwrap();
+        }
+
+        assert_eq!(complex.skeleton(0).basis.len(), 5);
+        assert_eq!(complex.skeleton(1).basis.len(), 11);
+
+        let cofaces: Vec<usize> = complex.cofaces(&simplex![1, 2]).map(|(i, _)| i).collect();
+        assert_eq!(cofaces, vec![7, 11, 12]);
+
+        assert_eq!(
+            simplices(&complex.star(&simplex![3])),
+            vec![
+                simplex![1],
+                simplex![2],
+                simplex![3],
+                simplex![4],
+                simplex![1, 2],
+                simplex![1, 3],
+                simplex![2, 3],
+                simplex![3, 4],
+                simplex![1, 2, 3],
+            ]
+        );
+        assert_eq!(
+            simplices(&complex.link(&simplex![3])),
+            vec![simplex![1], simplex![2], simplex![4], simplex![1, 2]]
+        );
+        assert_eq!(
+            simplices(&complex.link(&simplex![1, 2])),
+            vec![simplex![0], simplex![3]]
+        );
+        assert_eq!(
+            simplices(&complex.closure(&[simplex![0, 1], simplex![3, 4]])),
+            vec![
+                simplex![0],
+                simplex![1],
+                simplex![3],
+                simplex![4],
+                simplex![0, 1],
+                simplex![3, 4],
+            ]
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let mut complex = Complex::<IndexedVec<Simplex>, Simplex>::new();
+        for s in vec![
+            simplex![0],
+            simplex![1],
+            simplex![2],
+            simplex![0, 1],
+            simplex![1, 2],
+            simplex![0, 2],
+            simplex![0, 1, 2],
+        ] {
+            complex.push(s).unwrap();
+        }
+
+        let json = serde_json::to_string(&complex).unwrap();
+        let read: Complex<IndexedVec<Simplex>, Simplex> = serde_json::from_str(&json).unwrap();
+        assert_eq!(simplices(&read), simplices(&complex));
+    }
+}
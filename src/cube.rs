@@ -0,0 +1,900 @@
+use crate::boundary_matrix::BoundaryMatrix;
+use crate::complex::Complex;
+use crate::filtration::{compare_values, Filtration};
+use crate::indexed_vec::IndexedVec;
+use crate::sign::Sign;
+use crate::traits::*;
+use crate::Persistence;
+use failure::Fail;
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+
+/// An error in building a cubical filtration on a grid
+#[derive(Debug, Fail)]
+pub enum CubeError {
+    #[fail(display = "the grid has no axes")]
+    NoAxes,
+    #[fail(display = "the voxels have different numbers of coordinates")]
+    DimensionMismatch,
+    #[fail(display = "the number of the values does not match the shape")]
+    WrongLength,
+    #[fail(display = "the grid is too large")]
+    TooLarge,
+}
+
+/// Elementary interval
+///
+/// This struct represents an elementary interval, which is either
+/// a unit interval `[a, a+1]` or a degenerate interval `[a, a]`.
+/// If the interval lies on a periodic axis of period `n`, the points are taken modulo `n`,
+/// so the upper end of `[n-1, n]` is identified with `0`.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub struct Interval {
+    lower: usize,
+    nondegenerate: bool,
+    period: Option<usize>,
+}
+
+impl Interval {
+    /// Creates the unit interval `[lower, lower+1]`.
+    #[inline]
+    pub fn unit(lower: usize) -> Interval {
+        Interval {
+            lower: lower,
+            nondegenerate: true,
+            period: None,
+        }
+    }
+
+    /// Creates the degenerate interval `[point, point]`.
+    #[inline]
+    pub fn degenerate(point: usize) -> Interval {
+        Interval {
+            lower: point,
+            nondegenerate: false,
+            period: None,
+        }
+    }
+
+    /// Puts `self` on a periodic axis of period `period`.
+    ///
+    /// The period must be at least 2 and greater than the lower end of `self`.
+    pub fn periodic(self, period: usize) -> Interval {
+        assert!(period >= 2 && self.lower < period);
+        Interval {
+            period: Some(period),
+            ..self
+        }
+    }
+
+    #[inline]
+    pub fn lower(&self) -> usize {
+        self.lower
+    }
+
+    #[inline]
+    pub fn upper(&self) -> usize {
+        match (self.nondegenerate, self.period) {
+            (false, _) => self.lower,
+            (true, Some(period)) => (self.lower + 1) % period,
+            (true, None) => self.lower + 1,
+        }
+    }
+
+    #[inline]
+    pub fn period(&self) -> Option<usize> {
+        self.period
+    }
+
+    #[inline]
+    pub fn is_degenerate(&self) -> bool {
+        !self.nondegenerate
+    }
+
+    /// Returns the degenerate interval at the lower end of `self`.
+    #[inline]
+    pub fn lower_face(&self) -> Interval {
+        Interval {
+            lower: self.lower,
+            nondegenerate: false,
+            period: self.period,
+        }
+    }
+
+    /// Returns the degenerate interval at the upper end of `self`.
+    #[inline]
+    pub fn upper_face(&self) -> Interval {
+        Interval {
+            lower: self.upper(),
+            nondegenerate: false,
+            period: self.period,
+        }
+    }
+
+    /// Checks whether `self` is contained in `other`.
+    pub fn is_subset_of(&self, other: &Interval) -> bool {
+        if self.nondegenerate {
+            self == other
+        } else {
+            self.period == other.period
+                && (self.lower == other.lower() || self.lower == other.upper())
+        }
+    }
+}
+
+impl std::fmt::Display for Interval {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.nondegenerate {
+            write!(f, "[{},{}]", self.lower, self.upper())
+        } else {
+            write!(f, "[{}]", self.lower)
+        }
+    }
+}
+
+/// The struct represents elementary cube
+///
+/// This struct represents an oriented elementary cube, which is a product of elementary intervals.
+/// The cubes in a complex are positively oriented and the faces produced by
+/// the boundary iterator carry the signs of the boundary operator as their orientations.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Cube {
+    intervals: Vec<Interval>,
+    orientation: Sign,
+}
+
+impl std::fmt::Display for Cube {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.orientation.is_negative() {
+            write!(f, "-")?;
+        }
+        for i in 0..self.intervals.len() {
+            write!(f, "{}", self.intervals[i])?;
+            if i < self.intervals.len() - 1 {
+                write!(f, "x")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Cube {
+    /// Create new positively oriented cube from intervals.
+    pub fn new(intervals: Vec<Interval>) -> Cube {
+        assert!(!intervals.is_empty());
+        Cube {
+            intervals: intervals,
+            orientation: Sign::positive(),
+        }
+    }
+
+    /// Create the top-dimensional cube whose lower corner is `lower`.
+    ///
+    /// # Example
+    /// ```
+    /// use teia::traits::*;
+    /// use teia::cube::Cube;
+    ///
+    /// let voxel = Cube::voxel(&[1, 2, 3]);
+    /// assert_eq!(voxel.dimension(), 3);
+    /// assert_eq!(format!("{}", voxel), "[1,2]x[2,3]x[3,4]");
+    /// ```
+    pub fn voxel(lower: &[usize]) -> Cube {
+        Cube::new(lower.iter().map(|a| Interval::unit(*a)).collect())
+    }
+
+    /// Create the vertex at `coords`.
+    pub fn vertex(coords: &[usize]) -> Cube {
+        Cube::new(coords.iter().map(|a| Interval::degenerate(*a)).collect())
+    }
+
+    #[inline]
+    pub fn intervals(&self) -> &[Interval] {
+        &self.intervals
+    }
+
+    #[inline]
+    pub fn orientation(&self) -> Sign {
+        self.orientation
+    }
+
+    /// Returns the dimension of the space in which `self` is embedded.
+    #[inline]
+    pub fn embedding_dimension(&self) -> usize {
+        self.intervals.len()
+    }
+
+    /// Returns all the faces of `self` including itself.
+    ///
+    /// The faces are positively oriented.
+    pub fn faces(&self) -> Vec<Cube> {
+        let mut faces = vec![Vec::with_capacity(self.intervals.len())];
+        for interval in self.intervals.iter() {
+            let choices = if interval.is_degenerate() {
+                vec![*interval]
+            } else {
+                vec![interval.lower_face(), interval.upper_face(), *interval]
+            };
+            faces = faces
+                .into_iter()
+                .flat_map(|face| {
+                    choices.iter().map(move |choice| {
+                        let mut face = face.clone();
+                        face.push(*choice);
+                        face
+                    })
+                })
+                .collect();
+        }
+        faces.into_iter().map(Cube::new).collect()
+    }
+}
+
+impl<'a> ChainGeneratorBoundary<'a, Cube> for Cube {
+    type BoundaryIter = Boundary<'a>;
+
+    /// Returns an iterator that produces the signed faces of the boundary of `self`.
+    fn boundary(&'a self) -> Boundary<'a> {
+        Boundary {
+            cube: self,
+            axis: 0,
+            upper: false,
+            sign: self.orientation,
+        }
+    }
+}
+
+impl ChainGenerator for Cube {
+    /// Returns the dimension of cube
+    ///
+    /// # Example
+    /// ```
+    /// use teia::traits::*;
+    /// use teia::cube::{Cube, Interval};
+    ///
+    /// let q = Cube::new(vec![Interval::unit(0), Interval::degenerate(3), Interval::unit(1)]);
+    /// assert_eq!(q.dimension(), 2);
+    /// ```
+    fn dimension(&self) -> usize {
+        self.intervals.iter().filter(|i| !i.is_degenerate()).count()
+    }
+
+    /// Returns the coefficient of the inner production.
+    ///
+    /// It returns the product of the orientations if the cubes have the same intervals.
+    fn inner_prod(&self, other: &Cube) -> Sign {
+        if self.intervals == other.intervals {
+            self.orientation * other.orientation
+        } else {
+            Sign::zero()
+        }
+    }
+
+    /// Checks whether `self` is a face of `other`
+    ///
+    /// # Example
+    /// ```
+    /// use teia::traits::*;
+    /// use teia::cube::Cube;
+    ///
+    /// let q = Cube::voxel(&[0, 0]);
+    /// let v = Cube::vertex(&[1, 0]);
+    ///
+    /// assert_eq!(v.is_face_of(&q), true);
+    /// assert_eq!(q.is_face_of(&v), false);
+    /// ```
+    fn is_face_of(&self, other: &Cube) -> bool {
+        self.intervals.len() == other.intervals.len()
+            && self
+                .intervals
+                .iter()
+                .zip(other.intervals.iter())
+                .all(|(s, t)| s.is_subset_of(t))
+    }
+}
+
+/// An iterator that produces the signed faces of the boundary of a cube.
+///
+/// For the `j`-th nondegenerate interval `[a, a+1]`, the face with `[a+1]` has the sign `(-1)^j`
+/// and the face with `[a]` has the opposite sign.
+pub struct Boundary<'a> {
+    cube: &'a Cube,
+    axis: usize,
+    upper: bool,
+    sign: Sign,
+}
+
+impl<'a> Iterator for Boundary<'a> {
+    type Item = Cube;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.axis < self.cube.intervals.len() {
+            let interval = self.cube.intervals[self.axis];
+            if interval.is_degenerate() {
+                self.axis += 1;
+                continue;
+            }
+
+            let mut intervals = self.cube.intervals.clone();
+            let face = if self.upper {
+                intervals[self.axis] = interval.upper_face();
+                self.upper = false;
+                self.axis += 1;
+                let face = Cube {
+                    intervals: intervals,
+                    orientation: self.sign,
+                };
+                self.sign *= Sign::negative();
+                face
+            } else {
+                intervals[self.axis] = interval.lower_face();
+                self.upper = true;
+                Cube {
+                    intervals: intervals,
+                    orientation: self.sign * Sign::negative(),
+                }
+            };
+            return Some(face);
+        }
+        None
+    }
+}
+
+/// Builds the cubical complex of the voxels whose lower corners are given.
+///
+/// The complex consists of the voxels and all their faces.
+/// The cubes are ordered by dimension, so the complex is filtered.
+pub fn voxel_complex(voxels: &[Vec<usize>]) -> Complex<IndexedVec<Cube>, Cube> {
+    let periods = match voxels.first() {
+        Some(voxel) => vec![None; voxel.len()],
+        None => Vec::new(),
+    };
+    voxel_complex_periodic(voxels, &periods)
+}
+
+/// Builds the cubical complex of the voxels with periodic boundary conditions.
+///
+/// The `i`-th element of `periods` is the period of the `i`-th axis,
+/// or `None` if the axis is not periodic. The coordinates of the voxels on a periodic axis
+/// must be less than its period. For example, the voxels of an `n x n x n` grid with
+/// all the axes of period `n` form a 3-torus.
+pub fn voxel_complex_periodic(
+    voxels: &[Vec<usize>],
+    periods: &[Option<usize>],
+) -> Complex<IndexedVec<Cube>, Cube> {
+    let mut cubes = BTreeSet::new();
+    for voxel in voxels.iter() {
+        assert_eq!(voxel.len(), periods.len());
+        let intervals = voxel
+            .iter()
+            .zip(periods.iter())
+            .map(|(lower, period)| match period {
+                Some(period) => Interval::unit(*lower).periodic(*period),
+                None => Interval::unit(*lower),
+            })
+            .collect();
+        for face in Cube::new(intervals).faces().into_iter() {
+            cubes.insert((face.dimension(), face.intervals));
+        }
+    }
+
+    let mut complex = Complex::new();
+    for (_, intervals) in cubes.into_iter() {
+        complex.push_unchecked(Cube::new(intervals));
+    }
+    complex
+}
+
+/// Filtration of the cubes of a grid
+///
+/// A cube of the grid is identified by its doubled coordinates, where an even coordinate `2a`
+/// is the degenerate interval `[a, a]` and an odd coordinate `2a + 1` is the unit interval
+/// `[a, a + 1]`, both shifted by the lower corner `origin` of the grid. The id of a cube is
+/// the row-major index of its doubled coordinates, so the faces of a cube are found from its
+/// id without searching the complex.
+/// The cubes are sorted by the value and then by the dimension, and the cubes with the same
+/// value and dimension are in the order of their ids.
+#[derive(Debug, Clone)]
+pub struct CubicalFiltration<T> {
+    origin: Vec<usize>,
+    extent: Vec<usize>,
+    ids: Vec<usize>,
+    pub values: Vec<T>,
+}
+
+impl<T> CubicalFiltration<T> {
+    /// Creates the filtration of the cubes with their ids and values.
+    ///
+    /// The ids must be ascending.
+    fn from_cells(origin: Vec<usize>, extent: Vec<usize>, cells: Vec<(usize, T)>) -> Self
+    where
+        T: PartialOrd,
+    {
+        let mut cells: Vec<(usize, usize, T)> = cells
+            .into_iter()
+            .map(|(id, value)| (grid_dimension(&extent, id), id, value))
+            .collect();
+        cells.sort_by(|(s, _, a), (t, _, b)| compare_values(a, b).then_with(|| s.cmp(t)));
+
+        let mut filt = CubicalFiltration {
+            origin: origin,
+            extent: extent,
+            ids: Vec::with_capacity(cells.len()),
+            values: Vec::with_capacity(cells.len()),
+        };
+        for (_, id, value) in cells.into_iter() {
+            filt.ids.push(id);
+            filt.values.push(value);
+        }
+        filt
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Returns the filtration value of the cube at `index`.
+    #[inline]
+    pub fn value(&self, index: usize) -> Option<&T> {
+        self.values.get(index)
+    }
+
+    /// Returns the dimension of the cube at `index`.
+    pub fn dimension(&self, index: usize) -> usize {
+        grid_dimension(&self.extent, self.ids[index])
+    }
+
+    /// Returns the cube at `index`.
+    pub fn cube(&self, index: usize) -> Cube {
+        let mut id = self.ids[index];
+        let mut intervals = vec![Interval::degenerate(0); self.extent.len()];
+        for axis in (0..self.extent.len()).rev() {
+            let c = id % self.extent[axis];
+            id /= self.extent[axis];
+            let lower = self.origin[axis] + c / 2;
+            intervals[axis] = if c % 2 == 1 {
+                Interval::unit(lower)
+            } else {
+                Interval::degenerate(lower)
+            };
+        }
+        Cube::new(intervals)
+    }
+
+    /// Builds the boundary matrix whose `i`-th column is the boundary of the `i`-th cube.
+    ///
+    /// The faces of a cube are the cubes whose ids differ by the stride of an axis along
+    /// which the cube is a unit interval.
+    pub fn boundary_matrix(&self) -> BoundaryMatrix {
+        let strides = grid_strides(&self.extent);
+        let mut positions: Vec<(usize, usize)> = self
+            .ids
+            .iter()
+            .enumerate()
+            .map(|(index, id)| (*id, index))
+            .collect();
+        positions.sort();
+        let position = |id: usize| {
+            let found = positions.binary_search_by_key(&id, |(id, _)| *id);
+            positions[found.expect("a face of a cube is missing")].1
+        };
+
+        let mut matrix = BoundaryMatrix::new();
+        for id in self.ids.iter() {
+            let mut column = Vec::new();
+            let mut rest = *id;
+            for axis in (0..self.extent.len()).rev() {
+                if (rest % self.extent[axis]) % 2 == 1 {
+                    column.push(position(id - strides[axis]));
+                    column.push(position(id + strides[axis]));
+                }
+                rest /= self.extent[axis];
+            }
+            matrix.push(column.len() / 2, column);
+        }
+        matrix
+    }
+
+    /// Computes the persistence diagram.
+    ///
+    /// It returns pairs of the dimension and the persistence pair of filtration values.
+    /// The pairs whose birth and death values are equal are not removed.
+    pub fn diagram(&self) -> Vec<(usize, Persistence<T>)>
+    where
+        T: Clone,
+    {
+        self.boundary_matrix()
+            .pairs()
+            .into_iter()
+            .map(|(dim, Persistence(birth, death))| {
+                let birth = self.values[birth].clone();
+                let death = death.map(|death| self.values[death].clone());
+                (dim, Persistence(birth, death))
+            })
+            .collect()
+    }
+
+    /// Converts `self` to the filtration of the complex of the cubes.
+    pub fn to_filtration(&self) -> Filtration<Cube, T>
+    where
+        T: Clone,
+    {
+        let mut filt = Filtration::new();
+        for (index, value) in self.values.iter().enumerate() {
+            filt.push_unchecked(self.cube(index), value.clone());
+        }
+        filt
+    }
+}
+
+/// Returns the number of the odd coordinates of the cube of `id`.
+fn grid_dimension(extent: &[usize], mut id: usize) -> usize {
+    let mut dim = 0;
+    for size in extent.iter().rev() {
+        dim += (id % size) % 2;
+        id /= size;
+    }
+    dim
+}
+
+/// Returns the strides of the row-major order of the grid.
+fn grid_strides(extent: &[usize]) -> Vec<usize> {
+    let mut strides = vec![1; extent.len()];
+    for axis in (0..extent.len().saturating_sub(1)).rev() {
+        strides[axis] = strides[axis + 1] * extent[axis + 1];
+    }
+    strides
+}
+
+/// Returns the number of the cubes of the grid of the doubled extents if it fits in `usize`.
+fn grid_size(extent: &[usize]) -> Result<usize, CubeError> {
+    extent
+        .iter()
+        .try_fold(1usize, |size, e| size.checked_mul(*e))
+        .ok_or(CubeError::TooLarge)
+}
+
+/// Calls `f` with the id and the doubled coordinates of every cube of the grid in order.
+fn for_each_cube<F: FnMut(usize, &[usize])>(extent: &[usize], size: usize, mut f: F) {
+    let mut coords = vec![0; extent.len()];
+    for id in 0..size {
+        f(id, &coords);
+        for (coord, e) in coords.iter_mut().zip(extent.iter()).rev() {
+            *coord += 1;
+            if *coord < *e {
+                break;
+            }
+            *coord = 0;
+        }
+    }
+}
+
+/// Builds the filtration of the cubical complex of the voxels with their values.
+///
+/// Each voxel is given by its lower corner, and each face of the voxels has the least value
+/// of the voxels containing it. The grid of the filtration is the bounding box of the voxels.
+pub fn voxel_filtration<T>(voxels: &[(Vec<usize>, T)]) -> Result<CubicalFiltration<T>, CubeError>
+where
+    T: PartialOrd + Clone,
+{
+    let dim = match voxels.first() {
+        Some((lower, _)) => lower.len(),
+        None => {
+            return Ok(CubicalFiltration::from_cells(
+                Vec::new(),
+                Vec::new(),
+                Vec::new(),
+            ))
+        }
+    };
+    if dim == 0 {
+        return Err(CubeError::NoAxes);
+    }
+    if voxels.iter().any(|(lower, _)| lower.len() != dim) {
+        return Err(CubeError::DimensionMismatch);
+    }
+
+    let mut origin = voxels[0].0.clone();
+    let mut upper = voxels[0].0.clone();
+    for (lower, _) in voxels.iter() {
+        for axis in 0..dim {
+            origin[axis] = origin[axis].min(lower[axis]);
+            upper[axis] = upper[axis].max(lower[axis]);
+        }
+    }
+    // The doubled coordinates of the upper corners of the voxels are `2 * (upper - origin) + 2`.
+    let extent = origin
+        .iter()
+        .zip(upper.iter())
+        .map(|(o, u)| (u - o).checked_mul(2).and_then(|e| e.checked_add(3)))
+        .collect::<Option<Vec<usize>>>()
+        .ok_or(CubeError::TooLarge)?;
+    grid_size(&extent)?;
+    let strides = grid_strides(&extent);
+
+    let mut cells = Vec::new();
+    for (lower, value) in voxels.iter() {
+        let base: usize = (0..dim)
+            .map(|axis| 2 * (lower[axis] - origin[axis]) * strides[axis])
+            .sum();
+        // The faces of a voxel are the offsets of 0, 1 or 2 along each axis.
+        let mut offsets = vec![0];
+        for stride in strides.iter() {
+            offsets = offsets
+                .into_iter()
+                .flat_map(|offset| (0..3).map(move |k| offset + k * stride))
+                .collect();
+        }
+        cells.extend(offsets.into_iter().map(|offset| (base + offset, value)));
+    }
+    cells.sort_by_key(|(id, _)| *id);
+
+    let mut least: Vec<(usize, T)> = Vec::new();
+    for (id, value) in cells.into_iter() {
+        match least.last_mut() {
+            Some((last, min)) if *last == id => {
+                if compare_values(value, min) == Ordering::Less {
+                    *min = value.clone();
+                }
+            }
+            _ => least.push((id, value.clone())),
+        }
+    }
+
+    Ok(CubicalFiltration::from_cells(origin, extent, least))
+}
+
+/// Builds the filtration of the cubical complex of the voxels of a dense grid.
+///
+/// The grid has `shape[i]` voxels along the `i`-th axis and `values` lists the values of
+/// the voxels in row-major order, i.e. the last coordinate changes fastest. Each face of
+/// the voxels has the least value of the voxels containing it.
+pub fn voxel_grid_filtration<T>(
+    shape: &[usize],
+    values: &[T],
+) -> Result<CubicalFiltration<T>, CubeError>
+where
+    T: PartialOrd + Clone,
+{
+    let (extent, size) = check_grid(shape, values.len(), |s| {
+        s.checked_mul(2).and_then(|e| e.checked_add(1))
+    })?;
+    let strides = grid_strides(shape);
+
+    let mut cells = Vec::with_capacity(size);
+    for_each_cube(&extent, size, |id, coords| {
+        // The voxel `a` contains the unit interval `2a + 1` and the degenerate ones `2a`
+        // and `2a + 2`.
+        let mut bases = vec![0];
+        for axis in 0..shape.len() {
+            let c = coords[axis];
+            let voxels: &[usize] = if c % 2 == 1 {
+                &[c / 2][..]
+            } else if c == 0 {
+                &[0][..]
+            } else if c / 2 == shape[axis] {
+                &[c / 2 - 1][..]
+            } else {
+                &[c / 2 - 1, c / 2][..]
+            };
+            let stride = strides[axis];
+            bases = bases
+                .into_iter()
+                .flat_map(|base| voxels.iter().map(move |v| base + v * stride))
+                .collect();
+        }
+        let value = bases
+            .into_iter()
+            .map(|index| &values[index])
+            .min_by(|a, b| compare_values(*a, *b))
+            .unwrap();
+        cells.push((id, value.clone()));
+    });
+
+    Ok(CubicalFiltration::from_cells(
+        vec![0; shape.len()],
+        extent,
+        cells,
+    ))
+}
+
+/// Builds the lower-star filtration of the cubical grid with values on its vertices.
+///
+/// The grid has `shape[i]` vertices along the `i`-th axis and `values` lists the values of
+/// the vertices in row-major order, i.e. the last coordinate changes fastest. Each cube of
+/// the grid has the greatest value of its vertices.
+pub fn lower_star_grid_filtration<T>(
+    shape: &[usize],
+    values: &[T],
+) -> Result<CubicalFiltration<T>, CubeError>
+where
+    T: PartialOrd + Clone,
+{
+    let (extent, size) = check_grid(shape, values.len(), |s| s.checked_mul(2).map(|e| e - 1))?;
+    let strides = grid_strides(shape);
+
+    let mut cells = Vec::with_capacity(size);
+    for_each_cube(&extent, size, |id, coords| {
+        let mut bases = vec![0];
+        for axis in 0..shape.len() {
+            let c = coords[axis];
+            let vertices: &[usize] = if c % 2 == 1 {
+                &[c / 2, c / 2 + 1][..]
+            } else {
+                &[c / 2][..]
+            };
+            let stride = strides[axis];
+            bases = bases
+                .into_iter()
+                .flat_map(|base| vertices.iter().map(move |v| base + v * stride))
+                .collect();
+        }
+        let value = bases
+            .into_iter()
+            .map(|index| &values[index])
+            .max_by(|a, b| compare_values(*a, *b))
+            .unwrap();
+        cells.push((id, value.clone()));
+    });
+
+    Ok(CubicalFiltration::from_cells(
+        vec![0; shape.len()],
+        extent,
+        cells,
+    ))
+}
+
+/// Checks the shape of a dense grid and returns the doubled extents and the number of cubes.
+///
+/// The doubled extent of an axis is computed from its size by `extent`, which is called
+/// only for the non-empty axes.
+fn check_grid<F>(shape: &[usize], len: usize, extent: F) -> Result<(Vec<usize>, usize), CubeError>
+where
+    F: Fn(usize) -> Option<usize>,
+{
+    if shape.is_empty() {
+        return Err(CubeError::NoAxes);
+    }
+    if shape
+        .iter()
+        .try_fold(1usize, |size, s| size.checked_mul(*s))
+        != Some(len)
+    {
+        return Err(CubeError::WrongLength);
+    }
+    if len == 0 {
+        return Ok((vec![0; shape.len()], 0));
+    }
+    let extent = shape
+        .iter()
+        .map(|s| extent(*s))
+        .collect::<Option<Vec<usize>>>()
+        .ok_or(CubeError::TooLarge)?;
+    let size = grid_size(&extent)?;
+    Ok((extent, size))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::boundary_matrix::BoundaryMatrix;
+    use crate::cube::{
+        lower_star_grid_filtration, voxel_complex, voxel_complex_periodic, voxel_filtration,
+        voxel_grid_filtration, Cube, CubeError, Interval,
+    };
+    use crate::testing::betti_numbers;
+    use crate::traits::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_cube_boundary() {
+        let cube = Cube::voxel(&[0, 0, 0]);
+        let boundary = cube.boundary().collect::<Vec<Cube>>();
+        assert_eq!(boundary.len(), 6);
+        assert!(boundary.iter().all(|face| face.dimension() == 2));
+
+        // The boundary of the boundary vanishes with signs.
+        let mut coeffs = BTreeMap::new();
+        for face in boundary.iter() {
+            for edge in face.boundary() {
+                let sign = if edge.orientation().is_positive() { 1 } else { -1 };
+                *coeffs.entry(edge.intervals().to_vec()).or_insert(0) += sign;
+            }
+        }
+        assert_eq!(coeffs.len(), 12);
+        assert!(coeffs.values().all(|c| *c == 0));
+    }
+
+    #[test]
+    fn test_cube_face() {
+        let q = Cube::new(vec![Interval::unit(0), Interval::unit(2)]);
+        let e = Cube::new(vec![Interval::unit(0), Interval::degenerate(3)]);
+        assert!(e.is_face_of(&q));
+        assert!(!q.is_face_of(&e));
+        assert_eq!(q.faces().len(), 9);
+    }
+
+    #[test]
+    fn test_voxel_complex() {
+        // An annulus made of eight voxels around a hole.
+        let voxels = (0..3)
+            .flat_map(|x| (0..3).map(move |y| vec![x, y]))
+            .filter(|v| v != &vec![1, 1])
+            .collect::<Vec<_>>();
+        let complex = voxel_complex(&voxels);
+        assert_eq!(complex.basis.len(), 16 + 24 + 8);
+
+        assert_eq!(betti_numbers(&complex, 2), vec![1, 1, 0]);
+    }
+
+    #[test]
+    fn test_periodic_voxel_complex() {
+        let interval = Interval::unit(2).periodic(3);
+        assert_eq!(interval.upper(), 0);
+        assert!(Interval::degenerate(0).periodic(3).is_subset_of(&interval));
+
+        let voxels = (0..3)
+            .flat_map(|x| (0..3).map(move |y| vec![x, y]))
+            .collect::<Vec<_>>();
+
+        let cylinder = voxel_complex_periodic(&voxels, &[Some(3), None]);
+        assert_eq!(betti_numbers(&cylinder, 2), vec![1, 1, 0]);
+
+        let torus = voxel_complex_periodic(&voxels, &[Some(3), Some(3)]);
+        assert_eq!(torus.basis.len(), 9 + 18 + 9);
+        assert_eq!(betti_numbers(&torus, 2), vec![1, 2, 1]);
+
+        let voxels = (0..3)
+            .flat_map(|x| (0..3).flat_map(move |y| (0..3).map(move |z| vec![x, y, z])))
+            .collect::<Vec<_>>();
+        let torus = voxel_complex_periodic(&voxels, &[Some(3), Some(3), Some(3)]);
+        assert_eq!(betti_numbers(&torus, 3), vec![1, 3, 3, 1]);
+    }
+
+    #[test]
+    fn test_cubical_filtration() {
+        // A 2x3 grid of voxels with a hole where the value is high.
+        let shape = [2, 3];
+        let values = [1, 1, 1, 1, 7, 1];
+        let voxels = (0..6)
+            .map(|i| (vec![i / 3, i % 3], values[i]))
+            .collect::<Vec<_>>();
+        let dense = voxel_grid_filtration(&shape, &values).unwrap();
+        let sparse = voxel_filtration(&voxels).unwrap();
+        assert_eq!(dense.len(), 12 + 17 + 6);
+        assert_eq!(dense.values, sparse.values);
+        assert_eq!(dense.boundary_matrix(), sparse.boundary_matrix());
+
+        let filt = dense.to_filtration();
+        let matrix = BoundaryMatrix::from_complex(&filt.complex).unwrap();
+        assert_eq!(dense.boundary_matrix(), matrix);
+        assert_eq!(dense.diagram(), filt.diagram().unwrap());
+
+        let lower_star = lower_star_grid_filtration(&shape, &values).unwrap();
+        let filt = lower_star.to_filtration();
+        assert_eq!(lower_star.len(), 6 + 7 + 2);
+        assert_eq!(lower_star.diagram(), filt.diagram().unwrap());
+
+        match voxel_grid_filtration::<usize>(&[], &[]) {
+            Err(CubeError::NoAxes) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match voxel_grid_filtration(&shape, &values[1..]) {
+            Err(CubeError::WrongLength) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match voxel_filtration(&[(vec![0], 0), (vec![0, 1], 0)]) {
+            Err(CubeError::DimensionMismatch) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match voxel_filtration(&[(vec![0, 0], 0), (vec![usize::MAX, 0], 0)]) {
+            Err(CubeError::TooLarge) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use crate::geometry::squared_distance;
+
+/// The trait describes a finite metric space
+///
+/// The points of the space are indexed from `0` to `len() - 1`.
+pub trait Distance {
+    fn len(&self) -> usize;
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn distance(&self, i: usize, j: usize) -> f64;
+}
+
+/// Point cloud in the Euclidean space
+#[derive(Debug, Clone)]
+pub struct PointCloud {
+    points: Vec<Vec<f64>>,
+}
+
+impl PointCloud {
+    pub fn new(points: Vec<Vec<f64>>) -> Self {
+        PointCloud { points: points }
+    }
+
+    #[inline]
+    pub fn points(&self) -> &[Vec<f64>] {
+        &self.points
+    }
+}
+
+impl Distance for PointCloud {
+    #[inline]
+    fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    #[inline]
+    fn distance(&self, i: usize, j: usize) -> f64 {
+        squared_distance(&self.points[i], &self.points[j]).sqrt()
+    }
+}
+
+/// Symmetric matrix of distances
+///
+/// Only the strictly lower triangular part is stored. The distance between two points
+/// may be infinite, which means that they are never joined.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DistanceMatrix {
+    len: usize,
+    lower: Vec<f64>,
+}
+
+impl DistanceMatrix {
+    /// Creates a matrix of `len` points at infinite distance from each other.
+    pub fn new(len: usize) -> Self {
+        DistanceMatrix {
+            len: len,
+            lower: vec![f64::INFINITY; len * len.saturating_sub(1) / 2],
+        }
+    }
+
+    /// Creates a matrix from the entries of the strictly lower triangular part.
+    ///
+    /// The entries are ordered by rows, i.e. `d(1,0), d(2,0), d(2,1), d(3,0), ...`.
+    /// It returns `None` if the number of the entries is not a triangular number.
+    /// No entries give a single point.
+    pub fn from_lower_triangular(lower: Vec<f64>) -> Option<Self> {
+        let mut len = 1;
+        while len * (len - 1) / 2 < lower.len() {
+            len += 1;
+        }
+        if len * (len - 1) / 2 == lower.len() {
+            Some(DistanceMatrix {
+                len: len,
+                lower: lower,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Sets the distance between two distinct points.
+    pub fn set_distance(&mut self, i: usize, j: usize, distance: f64) {
+        assert!(i != j && i < self.len && j < self.len);
+        let (i, j) = if i > j { (i, j) } else { (j, i) };
+        self.lower[i * (i - 1) / 2 + j] = distance;
+    }
+}
+
+impl Distance for DistanceMatrix {
+    #[inline]
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    fn distance(&self, i: usize, j: usize) -> f64 {
+        if i == j {
+            0.0
+        } else if i > j {
+            self.lower[i * (i - 1) / 2 + j]
+        } else {
+            self.lower[j * (j - 1) / 2 + i]
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use crate::complex::Complex;
+use crate::filtration::Filtration;
+use crate::indexed_vec::IndexedVec;
+use crate::nerve::nerve;
+use crate::simplex::Simplex;
+use failure::Fail;
+
+/// An error in building a Dowker filtration
+#[derive(Debug, Fail)]
+pub enum DowkerError {
+    #[fail(display = "row {} has {} weights but row 0 has {}", row, found, expected)]
+    RaggedRow {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+/// Builds the Dowker complex of the binary relation.
+///
+/// The relation between `X` and `Y` is given by the rows of `relation`: `x` is related to `y`
+/// if `relation[x][y]` is true. The vertices of the complex are the elements of `X`, and
+/// a simplex is in the complex if its vertices are related to a common element of `Y`.
+/// The simplices of dimension greater than `max_dim` are not included.
+/// The simplices are ordered by dimension.
+pub fn dowker_complex(
+    relation: &[Vec<bool>],
+    max_dim: usize,
+) -> Complex<IndexedVec<Simplex>, Simplex> {
+    let cover: Vec<Vec<usize>> = relation
+        .iter()
+        .map(|row| {
+            row.iter()
+                .enumerate()
+                .filter(|(_, related)| **related)
+                .map(|(y, _)| y)
+                .collect()
+        })
+        .collect();
+    nerve(&cover, max_dim)
+}
+
+/// Builds the Dowker filtration of the weighted relation.
+///
+/// The relation at the value `t` relates `x` to `y` if `weights[x][y] <= t`. The filtration
+/// value of a simplex is the least `t` at which it is in the Dowker complex of the relation,
+/// i.e. the minimum over `y` of the maximum of `weights[x][y]` over the vertices `x`.
+/// The simplices of dimension greater than `max_dim` or with the value greater than
+/// `max_value` are not included. All the rows of `weights` must have the same length.
+pub fn dowker_filtration(
+    weights: &[Vec<f64>],
+    max_dim: usize,
+    max_value: f64,
+) -> Result<Filtration<Simplex, f64>, DowkerError> {
+    if let Some(first) = weights.first() {
+        if let Some((row, w)) = weights.iter().enumerate().find(|(_, w)| w.len() != first.len()) {
+            return Err(DowkerError::RaggedRow {
+                row: row,
+                expected: first.len(),
+                found: w.len(),
+            });
+        }
+    }
+
+    let mut elems = Vec::new();
+    let mut stack: Vec<(Vec<usize>, Vec<f64>)> = Vec::new();
+    for x in (0..weights.len()).rev() {
+        stack.push((vec![x], weights[x].clone()));
+    }
+
+    // Each entry of the stack holds a simplex and the maximum weights over its vertices.
+    while let Some((simplex, maximum)) = stack.pop() {
+        let value = maximum.iter().cloned().fold(f64::INFINITY, f64::min);
+        if value > max_value {
+            continue;
+        }
+        if simplex.len() <= max_dim {
+            let last = *simplex.last().unwrap();
+            for x in (last + 1..weights.len()).rev() {
+                let mut coface = simplex.clone();
+                coface.push(x);
+                let maximum = maximum
+                    .iter()
+                    .zip(weights[x].iter())
+                    .map(|(a, b)| a.max(*b))
+                    .collect();
+                stack.push((coface, maximum));
+            }
+        }
+        elems.push((Simplex::new(simplex), value));
+    }
+
+    Ok(Filtration::from_unsorted(elems))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dowker::{dowker_complex, dowker_filtration, DowkerError};
+    use crate::simplex;
+    use crate::simplex::Simplex;
+    use crate::testing::betti_numbers;
+    use crate::traits::*;
+
+    #[test]
+    fn test_dowker_duality() {
+        // Four points related to the edges of a square and one point related to a corner.
+        let relation = vec![
+            vec![true, false, false, true],
+            vec![true, true, false, false],
+            vec![false, true, true, false],
+            vec![false, false, true, true],
+            vec![true, false, false, false],
+        ];
+        let transpose: Vec<Vec<bool>> = (0..4)
+            .map(|y| relation.iter().map(|row| row[y]).collect())
+            .collect();
+
+        let complex = dowker_complex(&relation, 2);
+        let dual = dowker_complex(&transpose, 2);
+        assert_eq!(betti_numbers(&complex, 2), vec![1, 1, 0]);
+        assert_eq!(betti_numbers(&dual, 2), vec![1, 1, 0]);
+    }
+
+    #[test]
+    fn test_dowker_filtration() {
+        let weights = vec![
+            vec![1.0, 4.0, 2.0],
+            vec![3.0, 1.0, 5.0],
+            vec![2.0, 6.0, 1.0],
+        ];
+        let filt = dowker_filtration(&weights, 2, 4.0).unwrap();
+        let values: Vec<(Simplex, f64)> = filt
+            .complex
+            .basis
+            .iter()
+            .map(|(index, s)| (s.clone(), filt.values[index]))
+            .collect();
+        assert_eq!(
+            values,
+            vec![
+                (simplex![0], 1.0),
+                (simplex![1], 1.0),
+                (simplex![2], 1.0),
+                (simplex![0, 2], 2.0),
+                (simplex![0, 1], 3.0),
+                (simplex![1, 2], 3.0),
+                (simplex![0, 1, 2], 3.0),
+            ]
+        );
+
+        let ragged = vec![vec![1.0, 4.0, 2.0], vec![3.0, 1.0]];
+        match dowker_filtration(&ragged, 2, 4.0) {
+            Err(DowkerError::RaggedRow {
+                row: 1,
+                expected: 3,
+                found: 2,
+            }) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,241 @@
+use crate::complex::{Complex, ComplexError};
+use crate::indexed_vec::IndexedVec;
+use crate::pair::Pair;
+use crate::traits::*;
+use crate::z2reduce::Z2ColumnReduce;
+use crate::z2vector::Z2VectorVec;
+use crate::Persistence;
+use std::cmp::Ordering;
+
+/// Filtered complex
+///
+/// This struct represents a complex together with the filtration values of its basis.
+/// The `i`-th element of `values` is the filtration value of the `i`-th basis element,
+/// and the values must be non-decreasing along the basis.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(
+        try_from = "FiltrationData<G, T>",
+        bound(
+            serialize = "G: serde::Serialize, T: serde::Serialize",
+            deserialize = "G: serde::Deserialize<'de>, T: serde::Deserialize<'de> + PartialOrd"
+        )
+    )
+)]
+pub struct Filtration<G, T> {
+    pub complex: Complex<IndexedVec<G>, G>,
+    pub values: Vec<T>,
+}
+
+/// The unchecked form of `Filtration` read by the deserializer
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct FiltrationData<G, T> {
+    complex: Complex<IndexedVec<G>, G>,
+    values: Vec<T>,
+}
+
+#[cfg(feature = "serde")]
+impl<G, T: PartialOrd> std::convert::TryFrom<FiltrationData<G, T>> for Filtration<G, T> {
+    type Error = &'static str;
+
+    /// Accepts the values only if there is one for each basis element and they are
+    /// non-decreasing.
+    fn try_from(data: FiltrationData<G, T>) -> Result<Filtration<G, T>, Self::Error> {
+        if data.complex.basis.len() != data.values.len() {
+            Err("number of filtration values differs from the size of the complex")
+        } else if !data
+            .values
+            .windows(2)
+            .all(|w| w[0].partial_cmp(&w[1]).is_some_and(|o| o != Ordering::Greater))
+        {
+            Err("filtration values are not non-decreasing")
+        } else {
+            Ok(Filtration {
+                complex: data.complex,
+                values: data.values,
+            })
+        }
+    }
+}
+
+impl<G, T> Filtration<G, T>
+where
+    G: 'static + ChainGenerator,
+{
+    pub fn new() -> Self {
+        Filtration {
+            complex: Complex::new(),
+            values: Vec::new(),
+        }
+    }
+
+    /// Creates a filtration from unsorted pairs of a generator and its value.
+    ///
+    /// The pairs are sorted by the filtration value and then by the dimension.
+    /// The sort is stable, so the generators with the same value and dimension
+    /// keep the given order.
+    pub fn from_unsorted(mut elems: Vec<(G, T)>) -> Self
+    where
+        T: PartialOrd,
+    {
+        elems.sort_by(|(s, a), (t, b)| {
+            compare_values(a, b).then_with(|| s.dimension().cmp(&t.dimension()))
+        });
+
+        let mut filt = Filtration {
+            complex: Complex::new(),
+            values: Vec::with_capacity(elems.len()),
+        };
+        for (elem, value) in elems.into_iter() {
+            filt.push_unchecked(elem, value);
+        }
+        filt
+    }
+
+    /// Creates the lower-star filtration of the complex.
+    ///
+    /// The `i`-th element of `values` is the value of the vertex `i`, and the value of
+    /// a generator is the maximum of the values of its vertices.
+    pub fn lower_star<V>(complex: &Complex<V, G>, values: &[T]) -> Self
+    where
+        G: Clone + for<'a> ChainGeneratorVertices<'a>,
+        V: IndexedSet<G> + for<'a> IndexedSetIters<'a, G>,
+        T: PartialOrd + Clone,
+    {
+        Filtration::from_unsorted(
+            complex
+                .basis
+                .iter()
+                .map(|(_, gen)| {
+                    let value = gen
+                        .vertices()
+                        .map(|v| &values[*v])
+                        .max_by(|a, b| compare_values(*a, *b))
+                        .expect("a generator has no vertices");
+                    (gen.clone(), value.clone())
+                })
+                .collect(),
+        )
+    }
+
+    pub fn push(&mut self, elem: G, value: T) -> Result<(), ComplexError> {
+        self.complex.push(elem)?;
+        self.values.push(value);
+        Ok(())
+    }
+
+    pub fn push_unchecked(&mut self, elem: G, value: T) {
+        self.complex.push_unchecked(elem);
+        self.values.push(value);
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Returns the filtration value of the basis element at `index`.
+    #[inline]
+    pub fn value(&self, index: usize) -> Option<&T> {
+        self.values.get(index)
+    }
+
+    /// Computes the persistence diagram.
+    ///
+    /// It returns pairs of the dimension and the persistence pair of filtration values.
+    /// The pairs whose birth and death values are equal are not removed.
+    pub fn diagram(&self) -> Result<Vec<(usize, Persistence<T>)>, failure::Error>
+    where
+        G: PartialEq + for<'a> ChainGeneratorBoundary<'a, G>,
+        T: Clone,
+    {
+        let reduce = Z2ColumnReduce::<Z2VectorVec>::from_complex(&self.complex)?;
+
+        let diagram = Pair::new(&reduce, reduce.cycles())
+            .map(|(Persistence(birth, death), _)| {
+                let dim = self.complex.basis[birth].dimension();
+                let birth = self.values[birth].clone();
+                let death = death.map(|death| self.values[death].clone());
+                (dim, Persistence(birth, death))
+            })
+            .collect();
+
+        Ok(diagram)
+    }
+}
+
+impl<G: 'static + ChainGenerator, T> Default for Filtration<G, T> {
+    fn default() -> Self {
+        Filtration::new()
+    }
+}
+
+/// Compares two filtration values.
+///
+/// It panics if the values are not comparable, e.g. one of them is NaN.
+pub(crate) fn compare_values<T: PartialOrd>(a: &T, b: &T) -> Ordering {
+    a.partial_cmp(b).expect("filtration values are not comparable")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::filtration::Filtration;
+    use crate::simplex;
+    use crate::Persistence;
+
+    #[test]
+    fn test_filtration_diagram() {
+        let filt = Filtration::from_unsorted(vec![
+            (simplex![0, 1, 2], 3.0),
+            (simplex![0, 1], 1.0),
+            (simplex![1, 2], 1.0),
+            (simplex![0, 2], 2.0),
+            (simplex![0], 0.0),
+            (simplex![1], 0.0),
+            (simplex![2], 0.5),
+        ]);
+
+        let mut diagram = filt.diagram().unwrap();
+        diagram.retain(|(_, Persistence(birth, death))| Some(*birth) != *death);
+
+        assert_eq!(
+            diagram,
+            vec![
+                (0, Persistence(0.0, None)),
+                (0, Persistence(0.0, Some(1.0))),
+                (0, Persistence(0.5, Some(1.0))),
+                (1, Persistence(2.0, Some(3.0))),
+            ]
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_filtration_serde() {
+        use crate::simplex::Simplex;
+
+        let filt = Filtration::from_unsorted(vec![
+            (simplex![0, 1], 1.0),
+            (simplex![0], 0.0),
+            (simplex![1], 0.5),
+        ]);
+        let json = serde_json::to_string(&filt).unwrap();
+        let read: Filtration<Simplex, f64> = serde_json::from_str(&json).unwrap();
+        assert_eq!(read.values, filt.values);
+        assert_eq!(read.diagram().unwrap(), filt.diagram().unwrap());
+
+        let complex = r#"{"basis":{"vec":[{"vertices":[0]},{"vertices":[1]}],"start":0}}"#;
+        let decreasing = format!(r#"{{"complex":{},"values":[1.0,0.0]}}"#, complex);
+        assert!(serde_json::from_str::<Filtration<Simplex, f64>>(&decreasing).is_err());
+        let short = format!(r#"{{"complex":{},"values":[0.0]}}"#, complex);
+        assert!(serde_json::from_str::<Filtration<Simplex, f64>>(&short).is_err());
+    }
+}
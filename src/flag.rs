@@ -0,0 +1,243 @@
+use crate::filtration::Filtration;
+use crate::simplex::Simplex;
+use std::collections::BTreeMap;
+
+/// Weighted undirected graph
+///
+/// The vertices are indexed from `0` to `len() - 1` and both the vertices and the edges
+/// carry weights.
+#[derive(Debug, Clone, Default)]
+pub struct WeightedGraph {
+    vertex_weights: Vec<f64>,
+    edges: Vec<(usize, usize, f64)>,
+}
+
+impl WeightedGraph {
+    /// Creates a graph with `len` vertices of weight zero and no edges.
+    pub fn new(len: usize) -> Self {
+        Self::with_vertex_weights(vec![0.0; len])
+    }
+
+    /// Creates a graph with the weighted vertices and no edges.
+    pub fn with_vertex_weights(vertex_weights: Vec<f64>) -> Self {
+        WeightedGraph {
+            vertex_weights: vertex_weights,
+            edges: Vec::new(),
+        }
+    }
+
+    /// Creates a graph from the weighted adjacency lists.
+    ///
+    /// The list `adjacency[u]` holds the neighbors of `u` with the weights of the edges.
+    /// An edge may be listed at either or both of its ends.
+    pub fn from_adjacency_lists(adjacency: &[Vec<(usize, f64)>]) -> Self {
+        let mut graph = Self::new(adjacency.len());
+        for (u, neighbors) in adjacency.iter().enumerate() {
+            for (v, weight) in neighbors.iter() {
+                graph.add_edge(u, *v, *weight);
+            }
+        }
+        graph
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.vertex_weights.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.vertex_weights.is_empty()
+    }
+
+    #[inline]
+    pub fn vertex_weights(&self) -> &[f64] {
+        &self.vertex_weights
+    }
+
+    #[inline]
+    pub fn edges(&self) -> &[(usize, usize, f64)] {
+        &self.edges
+    }
+
+    /// Adds a vertex with the weight and returns its index.
+    pub fn add_vertex(&mut self, weight: f64) -> usize {
+        self.vertex_weights.push(weight);
+        self.vertex_weights.len() - 1
+    }
+
+    /// Adds an edge with the weight.
+    ///
+    /// Loops are ignored. If an edge is added more than once, the least weight is used.
+    pub fn add_edge(&mut self, u: usize, v: usize, weight: f64) {
+        assert!(u < self.len() && v < self.len());
+        if u != v {
+            self.edges.push((u, v, weight));
+        }
+    }
+
+    /// Builds the filtered flag complex of the graph.
+    ///
+    /// The value of a simplex is the maximum of the weights of its vertices and edges.
+    /// The simplices of dimension greater than `max_dim` or with the value greater than
+    /// `max_value` are not included. The vertices of the complex are the indices of
+    /// the vertices of the graph.
+    pub fn flag_filtration(&self, max_dim: usize, max_value: f64) -> Filtration<Simplex, f64> {
+        let labels: Vec<usize> = (0..self.len()).collect();
+        flag_filtration(
+            &labels,
+            &self.vertex_weights,
+            &self.edges,
+            max_dim,
+            max_value,
+        )
+    }
+}
+
+/// Builds the flag filtration of a weighted graph.
+///
+/// The value of a simplex is the maximum of the values of its vertices and edges.
+/// The simplices of dimension greater than `max_dim` or with the value greater than
+/// `max_value` are not included. If an edge is given more than once, the least value is used.
+/// The vertex `i` of the graph is labeled `labels[i]` in the complex.
+pub(crate) fn flag_filtration(
+    labels: &[usize],
+    vertex_values: &[f64],
+    edges: &[(usize, usize, f64)],
+    max_dim: usize,
+    max_value: f64,
+) -> Filtration<Simplex, f64> {
+    let n = vertex_values.len();
+    assert_eq!(labels.len(), n);
+
+    // The neighbors with larger indices.
+    let mut adjacency = vec![BTreeMap::new(); n];
+    for (u, v, value) in edges.iter() {
+        let (u, v) = if u < v { (*u, *v) } else { (*v, *u) };
+        if u == v || *value > max_value {
+            continue;
+        }
+        let entry = adjacency[u].entry(v).or_insert(*value);
+        if *value < *entry {
+            *entry = *value;
+        }
+    }
+
+    let mut elems = Vec::new();
+    for v in 0..n {
+        if vertex_values[v] > max_value {
+            continue;
+        }
+        elems.push((vec![v], vertex_values[v]));
+        if max_dim > 0 {
+            let candidates: Vec<usize> = adjacency[v].keys().cloned().collect();
+            expand(
+                &adjacency,
+                vertex_values,
+                vec![v],
+                vertex_values[v],
+                &candidates,
+                max_dim,
+                max_value,
+                &mut elems,
+            );
+        }
+    }
+
+    Filtration::from_unsorted(
+        elems
+            .into_iter()
+            .map(|(vertices, value)| {
+                let vertices = vertices.into_iter().map(|v| labels[v]).collect();
+                (Simplex::new(vertices), value)
+            })
+            .collect(),
+    )
+}
+
+/// Adds the cliques containing `clique` whose other vertices are in `candidates`.
+#[allow(clippy::too_many_arguments)]
+fn expand(
+    adjacency: &[BTreeMap<usize, f64>],
+    vertex_values: &[f64],
+    clique: Vec<usize>,
+    value: f64,
+    candidates: &[usize],
+    max_dim: usize,
+    max_value: f64,
+    elems: &mut Vec<(Vec<usize>, f64)>,
+) {
+    for (i, v) in candidates.iter().enumerate() {
+        let value = clique
+            .iter()
+            .map(|u| adjacency[*u][v])
+            .fold(value.max(vertex_values[*v]), f64::max);
+        if value > max_value {
+            continue;
+        }
+
+        let mut coclique = clique.clone();
+        coclique.push(*v);
+        elems.push((coclique.clone(), value));
+
+        if coclique.len() <= max_dim {
+            let next: Vec<usize> = candidates[i + 1..]
+                .iter()
+                .filter(|u| adjacency[*v].contains_key(u))
+                .cloned()
+                .collect();
+            expand(
+                adjacency,
+                vertex_values,
+                coclique,
+                value,
+                &next,
+                max_dim,
+                max_value,
+                elems,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::flag::WeightedGraph;
+    use crate::simplex::Simplex;
+    use crate::traits::*;
+    use crate::Persistence;
+
+    #[test]
+    fn test_weighted_graph() {
+        // A square whose diagonal appears last.
+        let graph = WeightedGraph::from_adjacency_lists(&[
+            vec![(1, 1.0), (3, 2.0), (2, 5.0)],
+            vec![(2, 3.0)],
+            vec![(3, 4.0)],
+            vec![(0, 6.0)],
+        ]);
+        let filt = graph.flag_filtration(2, f64::INFINITY);
+        assert_eq!(filt.len(), 4 + 5 + 2);
+
+        let basis = &filt.complex.basis;
+        let index = basis
+            .position_within(basis.index_range(), &Simplex::new(vec![0, 1, 2]))
+            .unwrap();
+        assert_eq!(filt.value(index), Some(&5.0));
+
+        let diagram = filt.diagram().unwrap();
+        assert!(diagram.contains(&(1, Persistence(4.0, Some(5.0)))));
+        let bars = diagram
+            .iter()
+            .filter(|(d, Persistence(birth, death))| *d == 1 && death != &Some(*birth))
+            .count();
+        assert_eq!(bars, 1);
+
+        let mut graph = WeightedGraph::with_vertex_weights(vec![0.0, 7.0]);
+        graph.add_edge(0, 1, 1.0);
+        graph.add_edge(0, 0, 2.0);
+        assert_eq!(graph.edges().len(), 1);
+        let filt = graph.flag_filtration(1, 5.0);
+        assert_eq!(filt.len(), 1);
+    }
+}
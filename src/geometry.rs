@@ -0,0 +1,146 @@
+/// Returns the squared Euclidean distance between `a` and `b`.
+pub fn squared_distance(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b.iter()).map(|(x, y)| (x - y) * (x - y)).sum()
+}
+
+/// Computes the smallest sphere passing through the given points.
+///
+/// It returns the center and the squared radius of the smallest sphere
+/// whose center lies in the affine hull of `points`.
+/// It returns `None` if the points are affinely dependent.
+pub fn circumsphere(points: &[&[f64]]) -> Option<(Vec<f64>, f64)> {
+    orthosphere(points, &vec![0.0; points.len()])
+}
+
+/// Computes the smallest sphere orthogonal to the given weighted points.
+///
+/// A weighted point `(p, w)` is a sphere centered at `p` with squared radius `w`.
+/// A sphere `(c, r2)` is orthogonal to it if `|c - p|^2 - w = r2`.
+/// It returns the center and the squared radius of the orthogonal sphere
+/// whose center lies in the affine hull of `points`. The squared radius may be negative.
+/// It returns `None` if the points are affinely dependent.
+pub fn orthosphere(points: &[&[f64]], weights: &[f64]) -> Option<(Vec<f64>, f64)> {
+    assert!(!points.is_empty());
+    assert_eq!(points.len(), weights.len());
+
+    let origin = points[0];
+    let vectors: Vec<Vec<f64>> = points[1..]
+        .iter()
+        .map(|p| p.iter().zip(origin.iter()).map(|(x, o)| x - o).collect())
+        .collect();
+
+    // The center is `origin + sum_j lambda_j v_j`, where
+    // `sum_j 2 (v_i . v_j) lambda_j = |v_i|^2 - w_i + w_0` for each `i`.
+    let gram: Vec<Vec<f64>> = vectors
+        .iter()
+        .map(|u| vectors.iter().map(|v| 2.0 * dot(u, v)).collect())
+        .collect();
+    let rhs: Vec<f64> = vectors
+        .iter()
+        .zip(weights[1..].iter())
+        .map(|(v, w)| dot(v, v) - w + weights[0])
+        .collect();
+    let lambda = solve(gram, rhs)?;
+
+    let mut center = origin.to_vec();
+    for (l, v) in lambda.iter().zip(vectors.iter()) {
+        for (c, x) in center.iter_mut().zip(v.iter()) {
+            *c += l * x;
+        }
+    }
+    let radius2 = squared_distance(&center, origin) - weights[0];
+
+    Some((center, radius2))
+}
+
+/// Computes the minimal enclosing ball of the given points.
+///
+/// It returns the center and the squared radius of the smallest ball containing `points`.
+/// The ball is the smallest circumsphere of a subset of the points that contains all of them,
+/// so the subsets are searched exhaustively. It is intended for a small number of points,
+/// such as the vertices of a simplex.
+///
+/// # Example
+/// ```
+/// use teia::geometry::minimal_enclosing_ball;
+///
+/// let (center, radius2) = minimal_enclosing_ball(&[&[0.0, 0.0], &[4.0, 0.0], &[2.0, 1.0]]);
+/// assert_eq!(center, vec![2.0, 0.0]);
+/// assert_eq!(radius2, 4.0);
+/// ```
+pub fn minimal_enclosing_ball(points: &[&[f64]]) -> (Vec<f64>, f64) {
+    assert!(!points.is_empty() && points.len() < 32);
+
+    let mut best: Option<(Vec<f64>, f64)> = None;
+    for mask in 1..(1u32 << points.len()) {
+        let support: Vec<&[f64]> = points
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| mask & (1 << i) != 0)
+            .map(|(_, p)| *p)
+            .collect();
+        let (center, radius2) = match circumsphere(&support) {
+            Some(sphere) => sphere,
+            None => continue,
+        };
+        if let Some((_, r2)) = best.as_ref() {
+            if *r2 <= radius2 {
+                continue;
+            }
+        }
+        let tolerance = 1e-12 * radius2.max(1.0);
+        if points
+            .iter()
+            .all(|p| squared_distance(p, &center) <= radius2 + tolerance)
+        {
+            best = Some((center, radius2));
+        }
+    }
+
+    best.expect("no enclosing ball is found")
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b.iter()).map(|(x, y)| x * y).sum()
+}
+
+/// Solves the linear equation `a x = b` by Gaussian elimination with partial pivoting.
+///
+/// It returns `None` if the matrix is singular.
+pub(crate) fn solve(mut a: Vec<Vec<f64>>, mut b: Vec<f64>) -> Option<Vec<f64>> {
+    let n = b.len();
+    let scale = a
+        .iter()
+        .flat_map(|row| row.iter())
+        .fold(0.0f64, |m, x| m.max(x.abs()));
+    let eps = 1e-12 * scale.max(f64::MIN_POSITIVE);
+
+    for col in 0..n {
+        let pivot = (col..n)
+            .max_by(|i, j| a[*i][col].abs().partial_cmp(&a[*j][col].abs()).unwrap())
+            .unwrap();
+        if a[pivot][col].abs() <= eps {
+            return None;
+        }
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+
+        for row in col + 1..n {
+            let factor = a[row][col] / a[col][col];
+            if factor != 0.0 {
+                let (upper, lower) = a.split_at_mut(row);
+                for (x, y) in lower[0][col..].iter_mut().zip(upper[col][col..].iter()) {
+                    *x -= factor * y;
+                }
+                b[row] -= factor * b[col];
+            }
+        }
+    }
+
+    let mut x = vec![0.0; n];
+    for row in (0..n).rev() {
+        let sum: f64 = (row + 1..n).map(|k| a[row][k] * x[k]).sum();
+        x[row] = (b[row] - sum) / a[row][row];
+    }
+    Some(x)
+}
@@ -1,6 +1,7 @@
 use crate::traits::{IndexedSet, IndexedSetIters};
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct IndexedVec<T> {
     vec: Vec<T>,
     start: usize,
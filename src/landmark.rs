@@ -0,0 +1,344 @@
+use crate::distance::Distance;
+use crate::filtration::compare_values;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+/// Selects `count` landmarks uniformly at random.
+///
+/// The selection is determined by `seed`. The landmarks are returned in ascending order.
+pub fn random_landmarks(len: usize, count: usize, seed: u64) -> Vec<usize> {
+    assert!(count <= len);
+
+    let mut state = seed;
+    let mut indices: Vec<usize> = (0..len).collect();
+    for i in 0..count {
+        let j = i + (splitmix64(&mut state) % (len - i) as u64) as usize;
+        indices.swap(i, j);
+    }
+    indices.truncate(count);
+    indices.sort();
+    indices
+}
+
+/// Selects `count` landmarks by the maxmin (farthest point) procedure.
+///
+/// The first landmark is `start` and each subsequent landmark is the point farthest from
+/// the landmarks selected so far. The landmarks are returned in the order of selection.
+/// It evaluates `O(len * count)` distances.
+pub fn maxmin_landmarks<D: Distance>(space: &D, count: usize, start: usize) -> Vec<usize> {
+    let (landmarks, _) = maxmin(space, start, count.min(space.len()));
+    landmarks
+}
+
+/// Computes the greedy permutation of the space.
+///
+/// It returns the points in the order of the maxmin procedure starting from `start`
+/// and their insertion radii, i.e. the distance from each point to the points preceding it.
+/// The insertion radius of `start` is the positive infinity.
+pub fn greedy_permutation<D: Distance>(space: &D, start: usize) -> (Vec<usize>, Vec<f64>) {
+    maxmin(space, start, space.len())
+}
+
+/// Runs the first `count` steps of the maxmin procedure.
+fn maxmin<D: Distance>(space: &D, start: usize, count: usize) -> (Vec<usize>, Vec<f64>) {
+    let tree = GreedyTree::new(space, start, count);
+    (tree.order, tree.radii)
+}
+
+/// The points selected by the maxmin procedure arranged in a tree
+///
+/// The parent of a selected point is its nearest point among those selected before it,
+/// and `cover` bounds the distances from each point to its descendants. The tree is used
+/// to find the selected points near a point without computing the distances to all of them.
+pub(crate) struct GreedyTree {
+    pub order: Vec<usize>,
+    pub radii: Vec<f64>,
+    position: Vec<Option<usize>>,
+    parent: Vec<Option<usize>>,
+    children: Vec<Vec<usize>>,
+    cover: Vec<f64>,
+}
+
+/// The relative tolerance for the rounding errors in the triangle inequality
+const SLACK: f64 = 1e-9;
+
+impl GreedyTree {
+    /// Runs the first `count` steps of the maxmin procedure starting from `start`.
+    ///
+    /// Every point belongs to the cell of its nearest selected point. When a point is
+    /// selected, only the cells of the selected points within twice the insertion radius can
+    /// lose points to it, so the other cells are not visited. The point farthest from the
+    /// selected points is taken from a heap of the farthest points of the cells.
+    /// Ties are broken by the smaller index.
+    pub(crate) fn new<D: Distance>(space: &D, start: usize, count: usize) -> Self {
+        let n = space.len();
+        let mut tree = GreedyTree {
+            order: Vec::with_capacity(count),
+            radii: Vec::with_capacity(count),
+            position: vec![None; n],
+            parent: vec![None; n],
+            children: vec![Vec::new(); n],
+            cover: vec![0.0; n],
+        };
+        if n == 0 || count == 0 {
+            return tree;
+        }
+
+        let mut owner = vec![start; n];
+        let mut nearest: Vec<f64> = (0..n).map(|i| space.distance(start, i)).collect();
+        let mut cells: Vec<Vec<usize>> = vec![Vec::new(); n];
+        cells[start] = (0..n).filter(|i| *i != start).collect();
+        let mut heap = BinaryHeap::new();
+        tree.select(start, None, f64::INFINITY);
+        push_farthest(&mut heap, &cells[start], &nearest, start);
+
+        while tree.order.len() < count {
+            let Farthest(radius, next, cell) = heap.pop().expect("no point is left");
+            if tree.position[next].is_some() || owner[next] != cell {
+                continue;
+            }
+
+            let mut near: Vec<usize> = tree
+                .range(space, next, 2.0 * radius, None)
+                .into_iter()
+                .map(|(center, _)| center)
+                .collect();
+            if !near.contains(&cell) {
+                near.push(cell);
+            }
+            tree.select(next, Some(cell), radius);
+
+            for center in near.into_iter() {
+                let mut kept = Vec::new();
+                for point in std::mem::take(&mut cells[center]).into_iter() {
+                    if point == next {
+                        continue;
+                    }
+                    let d = space.distance(point, next);
+                    if d < nearest[point] {
+                        nearest[point] = d;
+                        owner[point] = next;
+                        cells[next].push(point);
+                    } else {
+                        kept.push(point);
+                    }
+                }
+                cells[center] = kept;
+                push_farthest(&mut heap, &cells[center], &nearest, center);
+            }
+            push_farthest(&mut heap, &cells[next], &nearest, next);
+        }
+
+        tree
+    }
+
+    /// Returns the selected points within `radius` from `point` and their distances.
+    ///
+    /// If `before` is given, only the points at the positions less than it are returned.
+    pub(crate) fn range<D: Distance>(
+        &self,
+        space: &D,
+        point: usize,
+        radius: f64,
+        before: Option<usize>,
+    ) -> Vec<(usize, f64)> {
+        let before = before.unwrap_or(self.order.len());
+        let mut found = Vec::new();
+        if before == 0 {
+            return found;
+        }
+
+        let mut stack = vec![self.order[0]];
+        while let Some(x) = stack.pop() {
+            let d = space.distance(x, point);
+            if d <= radius {
+                found.push((x, d));
+            }
+            // The descendants of `x` are farther than `d - cover[x]` from `point`.
+            if d > (radius + self.cover[x]) * (1.0 + SLACK) {
+                continue;
+            }
+            // The children are ordered by their positions, which are greater than those
+            // of their ancestors.
+            for child in self.children[x].iter() {
+                if self.position[*child].unwrap() >= before {
+                    break;
+                }
+                stack.push(*child);
+            }
+        }
+        found
+    }
+
+    fn select(&mut self, point: usize, parent: Option<usize>, radius: f64) {
+        self.position[point] = Some(self.order.len());
+        self.order.push(point);
+        self.radii.push(radius);
+        self.parent[point] = parent;
+        if let Some(parent) = parent {
+            self.children[parent].push(point);
+        }
+
+        let mut node = point;
+        while let Some(parent) = self.parent[node] {
+            let reach = self.radii[self.position[node].unwrap()] + self.cover[node];
+            if reach <= self.cover[parent] {
+                break;
+            }
+            self.cover[parent] = reach;
+            node = parent;
+        }
+    }
+}
+
+/// Pushes the farthest point of the cell of `center` to the heap.
+fn push_farthest(heap: &mut BinaryHeap<Farthest>, cell: &[usize], nearest: &[f64], center: usize) {
+    if let Some(farthest) = cell
+        .iter()
+        .map(|point| Farthest(nearest[*point], *point, center))
+        .max()
+    {
+        heap.push(farthest);
+    }
+}
+
+/// The distance from a point to its nearest selected point, the point and the selected point
+///
+/// The order is by the distance and then by the reversed order of the points.
+struct Farthest(f64, usize, usize);
+
+impl PartialEq for Farthest {
+    fn eq(&self, other: &Farthest) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Farthest {}
+
+impl PartialOrd for Farthest {
+    fn partial_cmp(&self, other: &Farthest) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Farthest {
+    fn cmp(&self, other: &Farthest) -> Ordering {
+        compare_values(&self.0, &other.0).then_with(|| other.1.cmp(&self.1))
+    }
+}
+
+/// A pseudo-random number generator
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::distance::PointCloud;
+    use crate::distance::{Distance, DistanceMatrix};
+    use crate::landmark::{greedy_permutation, maxmin_landmarks, random_landmarks, GreedyTree};
+
+    fn scattered(n: usize) -> PointCloud {
+        PointCloud::new(
+            (0..n)
+                .map(|i| {
+                    let t = i as f64;
+                    vec![(t * 0.37).sin() * t, (t * 1.3).cos()]
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_random_landmarks() {
+        let landmarks = random_landmarks(100, 10, 1);
+        assert_eq!(landmarks.len(), 10);
+        assert!(landmarks.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(landmarks, random_landmarks(100, 10, 1));
+        assert_eq!(random_landmarks(10, 10, 5), (0..10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_maxmin_landmarks() {
+        let space = PointCloud::new((0..11).map(|i| vec![i as f64]).collect());
+        assert_eq!(maxmin_landmarks(&space, 3, 0), vec![0, 10, 5]);
+
+        let (order, radii) = greedy_permutation(&space, 0);
+        assert_eq!(order.len(), 11);
+        assert_eq!(&radii[1..4], &[10.0, 5.0, 2.0]);
+    }
+
+    #[test]
+    fn test_maxmin_landmarks_prefix() {
+        let space = scattered(50);
+        for start in &[0, 17] {
+            let (order, _) = greedy_permutation(&space, *start);
+            for k in &[0, 1, 2, 7, 50] {
+                assert_eq!(maxmin_landmarks(&space, *k, *start), &order[..*k]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_greedy_permutation_radii() {
+        let space = scattered(80);
+        let (order, radii) = greedy_permutation(&space, 3);
+        let mut sorted = order.clone();
+        sorted.sort();
+        assert_eq!(sorted, (0..80).collect::<Vec<_>>());
+        for i in 1..order.len() {
+            let nearest = order[..i]
+                .iter()
+                .map(|p| space.distance(*p, order[i]))
+                .fold(f64::INFINITY, f64::min);
+            let farthest = order[i..]
+                .iter()
+                .map(|q| {
+                    order[..i]
+                        .iter()
+                        .map(|p| space.distance(*p, *q))
+                        .fold(f64::INFINITY, f64::min)
+                })
+                .fold(0.0, f64::max);
+            assert_eq!(radii[i], nearest);
+            assert_eq!(radii[i], farthest);
+        }
+
+        // Points at infinite distance are selected after the others.
+        let mut matrix = DistanceMatrix::new(4);
+        matrix.set_distance(0, 1, 1.0);
+        matrix.set_distance(2, 3, 2.0);
+        let (order, radii) = greedy_permutation(&matrix, 0);
+        assert_eq!(order, vec![0, 2, 3, 1]);
+        assert_eq!(radii, vec![f64::INFINITY, f64::INFINITY, 2.0, 1.0]);
+    }
+
+    #[test]
+    fn test_greedy_tree_range() {
+        let space = scattered(60);
+        let tree = GreedyTree::new(&space, 0, 60);
+        for point in 0..60 {
+            for radius in &[0.5, 2.0, 10.0] {
+                for before in &[1, 20, 60] {
+                    let mut found: Vec<usize> = tree
+                        .range(&space, point, *radius, Some(*before))
+                        .into_iter()
+                        .map(|(x, _)| x)
+                        .collect();
+                    found.sort();
+                    let mut expected: Vec<usize> = tree.order[..*before]
+                        .iter()
+                        .cloned()
+                        .filter(|x| space.distance(*x, point) <= *radius)
+                        .collect();
+                    expected.sort();
+                    assert_eq!(found, expected);
+                }
+            }
+        }
+    }
+}
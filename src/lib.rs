@@ -1,5 +1,22 @@
+pub mod alpha;
+pub mod bifiltration;
+pub mod boundary_matrix;
+pub mod cech;
 pub mod complex;
+pub mod cube;
+pub mod distance;
+pub mod dowker;
+pub mod filtration;
+pub mod flag;
+pub mod geometry;
+pub mod landmark;
+pub mod mapper;
+pub mod nerve;
+pub mod operations;
+pub mod presentation;
+pub mod rips;
 pub mod simplex;
+pub mod witness;
 pub mod z2reduce;
 pub mod z2vector;
 
@@ -10,6 +27,9 @@ pub mod traits;
 
 pub mod reader;
 
+#[cfg(test)]
+mod testing;
+
 /// Persistence pair
 ///
 /// This struct represents persistence pair.
@@ -17,7 +37,8 @@ pub mod reader;
 /// the second element is the death filtration value.
 /// If the second element is `None`, it means that the cycle
 /// is an essential cycle.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Persistence<T>(pub T, pub Option<T>);
 
 impl<T> Persistence<T> {
@@ -1,13 +1,20 @@
 use teia::Persistence;
+use teia::boundary_matrix::BoundaryMatrix;
 use teia::traits::*;
+use teia::complex::Complex;
+use teia::indexed_vec::IndexedVec;
 use teia::simplex::Simplex;
-use teia::z2vector::{Z2Chain, Z2Vector, Z2VectorIter, Z2VectorVec};
+use teia::z2vector::{Z2Chain, Z2VectorIter, Z2VectorVec};
 use teia::z2reduce::Z2ColumnReduce;
 use teia::pair::Pair;
 use teia::reader;
+use teia::reader::mesh::MeshFormat;
+use teia::reader::simpcomp::ValuePosition;
+use teia::reader::volume::{DataType, Endianness};
+use teia::rips::rips_filtration;
 use std::collections::BTreeMap;
 use std::path::PathBuf;
-use std::io::BufReader;
+use std::io::{BufReader, BufWriter, Write};
 use std::fs::File;
 use structopt::StructOpt;
 
@@ -25,6 +32,8 @@ enum Command {
     /// The argument `INPUT` is the file path to the input file.
     /// The generators of each dimension are printed to the standard output.
     /// A generator is printed as a list of simplices in a line.
+    /// With `--format csv` or `--format json`, each generator is printed as its dimension and
+    /// the vertex lists of its simplices.
     #[structopt(name = "homology")]
     Homology(ComputeHomology),
     /// Compute the persistence of complex.
@@ -32,8 +41,61 @@ enum Command {
     /// This subcommand computes the persistent homology groups of the given complex.
     /// The argument `INPUT` is the file path to the input file.
     /// It prints birth index, death index and dimension separated by a whitespace.
+    /// With `--stages`, the blocks of the input separated by blank lines are the stages of
+    /// the filtration and it prints the birth and death stages instead of the indices.
+    /// With `--values`, each line of the input has the filtration value of the simplex and
+    /// it prints the birth and death values.
+    /// With `--perseus`, the input is in the given format of Perseus and it prints
+    /// the birth and death times.
+    /// With `--mesh` and `--scalar`, the input is a mesh and it prints the birth and death
+    /// values of the lower-star filtration by the scalar attribute of the vertices.
+    /// With `--format csv` or `--format json`, each pair is printed with its dimension, the
+    /// birth and death indices, the birth and death values if the filtration has values, and
+    /// the representative cycle as the vertex lists of its simplices (except for cubical inputs).
     #[structopt(name = "persistence")]
     Persistence(ComputePersistence),
+    /// Compute the persistence of the Vietoris-Rips filtration.
+    ///
+    /// This subcommand computes the persistent homology groups of the Vietoris-Rips
+    /// filtration of the given distance matrix or point cloud.
+    /// The argument `INPUT` is the file path to the input file, whose format is given by
+    /// `--input-format` with the same names as Ripser.
+    /// It prints birth value, death value and dimension separated by a whitespace,
+    /// omitting the pairs whose birth and death values are equal as Ripser does.
+    /// With `--format csv` or `--format json`, each pair is printed as in `persistence`.
+    #[structopt(name = "rips")]
+    Rips(ComputeRips),
+    /// Compute the persistence of the cubical filtration of an image.
+    ///
+    /// This subcommand computes the persistent homology groups of the cubical filtration of
+    /// the given image or volume.
+    /// The argument `INPUT` is the file path to a NumPy array (`.npy`) or, with
+    /// `--input-format raw`, a raw binary volume whose data type, shape and endianness are given by the options.
+    /// By default the elements are the voxels and each face has the least value of the voxels
+    /// containing it. With `--filtration lower-star`, the elements are the vertices and each
+    /// cube has the greatest value of its vertices.
+    /// It prints birth value, death value and dimension separated by a whitespace,
+    /// omitting the pairs whose birth and death values are equal.
+    /// With `--format csv` or `--format json`, each pair is printed as in `persistence`
+    /// without the representative cycle.
+    #[structopt(name = "image")]
+    Image(ComputeImage),
+    /// Convert a boundary matrix or compute its persistence.
+    ///
+    /// This subcommand reads a boundary matrix in the format given by `--from`, which is
+    /// `phat-ascii`, `phat-binary` or `dipha`, or the boundary matrix of a simplicial complex
+    /// with `--from complex`.
+    /// The argument `INPUT` is the file path to the input file.
+    /// With `--to` and `--output`, it writes the matrix in the format `phat-ascii`, `phat-binary`
+    /// or `dipha`, or its persistence diagram with `--to dipha-diagram`, or its persistence
+    /// pairs with `--to phat-pairs-ascii` or `--to phat-pairs-binary`. The filtration values
+    /// of the columns are those of the DIPHA input or the column indices.
+    /// Otherwise it prints birth, death and dimension separated by a whitespace, which are
+    /// the column indices or the filtration values of the DIPHA input.
+    /// With `--format csv` or `--format json`, each pair is printed as in `persistence`
+    /// without the representative cycle.
+    #[structopt(name = "boundary")]
+    Boundary(ComputeBoundary),
 }
 
 #[derive(Debug, StructOpt)]
@@ -41,84 +103,625 @@ struct ComputePersistence {
     /// Input file path
     #[structopt(name = "INPUT", parse(from_os_str))]
     input: PathBuf,
+    /// Read only the maximal simplices and add all their faces
+    #[structopt(long = "facets")]
+    facets: bool,
+    /// Read the blocks separated by blank lines as the stages of a filtration
+    #[structopt(long = "stages", raw(conflicts_with = "\"facets\""))]
+    stages: bool,
+    /// Read the filtration value at the position (`first` or `last`) of each line
+    #[structopt(
+        long = "values",
+        name = "POSITION",
+        raw(possible_values = "&[\"first\", \"last\"]"),
+        raw(conflicts_with_all = "&[\"facets\", \"stages\"]")
+    )]
+    values: Option<ValuePosition>,
+    /// Read the input in the format of Perseus (`nmfsimtop`, `cubtop` or `scubtop`)
+    #[structopt(
+        long = "perseus",
+        name = "FORMAT",
+        raw(possible_values = "&[\"nmfsimtop\", \"cubtop\", \"scubtop\"]"),
+        raw(conflicts_with_all = "&[\"facets\", \"stages\", \"POSITION\"]")
+    )]
+    perseus: Option<String>,
+    /// Read the input as a mesh in the format (`off`, `obj` or `ply`)
+    #[structopt(
+        long = "mesh",
+        name = "MESH",
+        raw(possible_values = "&[\"off\", \"obj\", \"ply\"]"),
+        raw(conflicts_with_all = "&[\"facets\", \"stages\", \"POSITION\", \"FORMAT\"]")
+    )]
+    mesh: Option<MeshFormat>,
+    /// Use the scalar attribute of the vertices of the mesh as the lower-star filtration
+    #[structopt(long = "scalar", name = "NAME", raw(requires = "\"MESH\""))]
+    scalar: Option<String>,
+    #[structopt(flatten)]
+    output: OutputOpt,
 }
 
-fn compute_persistence(cmd: ComputePersistence) {
-    let file = File::open(cmd.input).unwrap();
+fn compute_persistence(cmd: ComputePersistence) -> Result<(), failure::Error> {
+    let vertices = cycle_vertices(cmd.output.format);
 
-    let comp = reader::simpcomp::read_simpcomp_text(BufReader::new(file)).unwrap();
+    if cmd.stages {
+        let file = BufReader::new(File::open(&cmd.input)?);
+        let filt = reader::simpcomp::read_simpcomp_filtration_text(file)?;
+        let values = filt.values.iter().map(|v| *v as f64).collect::<Vec<f64>>();
+        print_pairs(&pair_records(&filt.complex, Some(&values), vertices)?, cmd.output.format);
+        return Ok(());
+    }
 
-    let reduce = Z2ColumnReduce::<Z2Chain<Z2VectorVec>>
-            ::from_complex_with(&comp, |index, chain| Z2Chain::new(index, chain)).unwrap();
+    if let Some(position) = cmd.values {
+        let file = BufReader::new(File::open(&cmd.input)?);
+        let filt = reader::simpcomp::read_simpcomp_values_text(file, position)?;
+        print_pairs(&pair_records(&filt.complex, Some(&filt.values), vertices)?, cmd.output.format);
+        return Ok(());
+    }
 
-    for (pers, chain) in Pair::new(&reduce, reduce.cycles()) {
-        match pers {
-            Persistence(birth, Some(death)) => {
-                print!("{} {}", birth, death);
+    if let Some(format) = cmd.perseus {
+        let file = BufReader::new(File::open(&cmd.input)?);
+        let records = match format.as_str() {
+            "nmfsimtop" => {
+                let filt = reader::perseus::read_perseus_simplicial(file)?;
+                let values = filt.values.iter().map(|v| *v as f64).collect::<Vec<f64>>();
+                pair_records(&filt.complex, Some(&values), vertices)?
+            },
+            "cubtop" => {
+                let filt = reader::perseus::read_perseus_cubical_dense(file)?;
+                let values = filt.values.iter().map(|v| *v as f64).collect::<Vec<f64>>();
+                matrix_records(&filt.boundary_matrix(), Some(&values))
             },
-            Persistence(birth, None) => {
-                print!("{} inf", birth);
+            "scubtop" => {
+                let filt = reader::perseus::read_perseus_cubical_sparse(file)?;
+                let values = filt.values.iter().map(|v| *v as f64).collect::<Vec<f64>>();
+                matrix_records(&filt.boundary_matrix(), Some(&values))
             },
+            _ => unreachable!(),
         };
-        println!(" {}", comp.basis[chain.chain.lowest().unwrap()].dimension());
+        print_pairs(&records, cmd.output.format);
+        return Ok(());
+    }
+
+    if let (Some(format), Some(name)) = (cmd.mesh, &cmd.scalar) {
+        let file = BufReader::new(File::open(&cmd.input)?);
+        let mesh = reader::mesh::read_mesh(file, format)?;
+        let values = mesh
+            .attribute(name)
+            .ok_or_else(|| failure::format_err!("the mesh has no attribute {}", name))?;
+        let filt = mesh.lower_star_filtration(values)?;
+        print_pairs(&pair_records(&filt.complex, Some(&filt.values), vertices)?, cmd.output.format);
+        return Ok(());
+    }
+
+    let comp = read_complex(&cmd.input, cmd.facets, cmd.mesh)?;
+    print_pairs(&pair_records(&comp, None, vertices)?, cmd.output.format);
+
+    Ok(())
+}
+
+/// The option of the output format shared by the subcommands
+#[derive(Debug, StructOpt)]
+struct OutputOpt {
+    /// Output format (`text`, `csv` or `json`)
+    #[structopt(
+        long = "format",
+        name = "OUTPUT",
+        default_value = "text",
+        raw(possible_values = "&[\"text\", \"csv\", \"json\"]")
+    )]
+    format: OutputFormat,
+}
+
+/// Output format of the persistence pairs and the generators
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum OutputFormat {
+    Text,
+    Csv,
+    Json,
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = failure::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(OutputFormat::Text),
+            "csv" => Ok(OutputFormat::Csv),
+            "json" => Ok(OutputFormat::Json),
+            _ => Err(failure::format_err!("unknown output format: {}", s)),
+        }
+    }
+}
+
+/// A persistence pair with its filtration values and representative cycle
+struct PairRecord {
+    dim: usize,
+    indices: Persistence<usize>,
+    values: Option<Persistence<f64>>,
+    cycle: Option<Vec<Vec<usize>>>,
+}
+
+/// Returns the function giving the vertices of a simplex in a cycle if the format prints cycles.
+fn cycle_vertices(format: OutputFormat) -> Option<fn(&Simplex) -> Vec<usize>> {
+    if format == OutputFormat::Text {
+        None
+    } else {
+        Some(simplex_vertices)
+    }
+}
+
+fn simplex_vertices(simplex: &Simplex) -> Vec<usize> {
+    simplex.vertices().cloned().collect()
+}
+
+/// Computes the persistence pairs of the complex.
+///
+/// The representative cycles are computed only if `vertices` is given.
+fn pair_records<G>(
+    comp: &Complex<IndexedVec<G>, G>,
+    values: Option<&[f64]>,
+    vertices: Option<fn(&G) -> Vec<usize>>,
+) -> Result<Vec<PairRecord>, failure::Error>
+where
+    G: 'static + PartialEq + ChainGenerator + for<'a> ChainGeneratorBoundary<'a, G>,
+{
+    let record = |pers: Persistence<usize>| PairRecord {
+        dim: comp.basis[pers.0].dimension(),
+        values: values.map(|values| Persistence(values[pers.0], pers.1.map(|death| values[death]))),
+        indices: pers,
+        cycle: None,
+    };
+
+    let records = match vertices {
+        Some(vertices) => {
+            let reduce = Z2ColumnReduce::<Z2Chain<Z2VectorVec>>
+                    ::from_complex_with(comp, |index, chain| Z2Chain::new(index, chain))?;
+            Pair::new(&reduce, reduce.cycles())
+                .map(|(pers, chain)| PairRecord {
+                    cycle: Some(chain.chain.iter().map(|index| vertices(&comp.basis[*index])).collect()),
+                    ..record(pers)
+                })
+                .collect()
+        },
+        None => {
+            let reduce = Z2ColumnReduce::<Z2VectorVec>::from_complex(comp)?;
+            Pair::new(&reduce, reduce.cycles())
+                .map(|(pers, _)| record(pers))
+                .collect()
+        },
+    };
+
+    Ok(records)
+}
+
+/// Returns the column indices of the persistence pairs which are not essential.
+fn finite_pairs(matrix: &BoundaryMatrix) -> Vec<(usize, usize)> {
+    matrix
+        .pairs()
+        .into_iter()
+        .filter_map(|(_, Persistence(birth, death))| death.map(|death| (birth, death)))
+        .collect()
+}
+
+/// Computes the persistence pairs of the boundary matrix.
+fn matrix_records(matrix: &BoundaryMatrix, values: Option<&[f64]>) -> Vec<PairRecord> {
+    matrix
+        .pairs()
+        .into_iter()
+        .map(|(dim, pers)| PairRecord {
+            dim: dim,
+            values: values.map(|values| Persistence(values[pers.0], pers.1.map(|death| values[death]))),
+            indices: pers,
+            cycle: None,
+        })
+        .collect()
+}
+
+/// Returns whether the birth and death values of the pair are equal.
+fn is_zero_length(record: &PairRecord) -> bool {
+    match record.values {
+        Some(Persistence(birth, death)) => death == Some(birth),
+        None => false,
     }
 }
 
+fn print_pairs(records: &[PairRecord], format: OutputFormat) {
+    match format {
+        OutputFormat::Text => {
+            for record in records.iter() {
+                match (&record.values, &record.indices) {
+                    (Some(Persistence(birth, Some(death))), _) => {
+                        println!("{} {} {}", birth, death, record.dim);
+                    },
+                    (Some(Persistence(birth, None)), _) => {
+                        println!("{} inf {}", birth, record.dim);
+                    },
+                    (None, Persistence(birth, Some(death))) => {
+                        println!("{} {} {}", birth, death, record.dim);
+                    },
+                    (None, Persistence(birth, None)) => {
+                        println!("{} inf {}", birth, record.dim);
+                    },
+                }
+            }
+        },
+        OutputFormat::Csv => {
+            println!("dim,birth,death,birth_value,death_value,cycle");
+            for record in records.iter() {
+                let (birth_value, death_value) = match &record.values {
+                    Some(Persistence(birth, death)) => (Some(*birth), *death),
+                    None => (None, None),
+                };
+                println!(
+                    "{},{},{},{},{},{}",
+                    record.dim,
+                    record.indices.0,
+                    csv_field(record.indices.1),
+                    csv_field(birth_value),
+                    csv_field(death_value),
+                    record.cycle.as_ref().map_or_else(String::new, |cycle| csv_cycle(cycle))
+                );
+            }
+        },
+        OutputFormat::Json => {
+            let objects = records
+                .iter()
+                .map(|record| {
+                    let (birth_value, death_value) = match &record.values {
+                        Some(Persistence(birth, death)) => (Some(*birth), *death),
+                        None => (None, None),
+                    };
+                    format!(
+                        "{{\"dim\":{},\"birth\":{},\"death\":{},\"birth_value\":{},\"death_value\":{},\"cycle\":{}}}",
+                        record.dim,
+                        record.indices.0,
+                        record.indices.1.map_or_else(|| "null".to_string(), |death| death.to_string()),
+                        json_value(birth_value),
+                        json_value(death_value),
+                        record.cycle.as_ref().map_or_else(|| "null".to_string(), |cycle| json_cycle(cycle))
+                    )
+                })
+                .collect::<Vec<_>>();
+            println!("[{}]", objects.join(","));
+        },
+    }
+}
+
+/// Formats an optional CSV field, which is empty if it is missing.
+fn csv_field<T: std::fmt::Display>(value: Option<T>) -> String {
+    value.map_or_else(String::new, |value| value.to_string())
+}
+
+/// Formats a cycle as the vertices of the simplices separated by `;`, e.g. `0 1;1 2;0 2`.
+fn csv_cycle(cycle: &[Vec<usize>]) -> String {
+    cycle
+        .iter()
+        .map(|vertices| vertices.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(" "))
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+/// Formats a filtration value as a JSON number, which is `null` if it is missing or not finite.
+fn json_value(value: Option<f64>) -> String {
+    match value {
+        Some(value) if value.is_finite() => value.to_string(),
+        _ => "null".to_string(),
+    }
+}
+
+/// Formats a cycle as a JSON array of the vertex lists of the simplices.
+fn json_cycle(cycle: &[Vec<usize>]) -> String {
+    let simplices = cycle
+        .iter()
+        .map(|vertices| {
+            let vertices = vertices.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+            format!("[{}]", vertices.join(","))
+        })
+        .collect::<Vec<_>>();
+    format!("[{}]", simplices.join(","))
+}
+
+#[derive(Debug, StructOpt)]
+struct ComputeRips {
+    /// Input file path
+    #[structopt(name = "INPUT", parse(from_os_str))]
+    input: PathBuf,
+    /// Input format
+    #[structopt(
+        long = "input-format",
+        default_value = "lower-distance",
+        raw(possible_values = "&[\"lower-distance\", \"distance\", \"sparse\", \"point-cloud\"]")
+    )]
+    input_format: String,
+    /// Maximum dimension of the persistent homology
+    #[structopt(long = "dim", default_value = "1")]
+    dim: usize,
+    /// Maximum diameter of the simplices
+    #[structopt(long = "threshold", default_value = "inf")]
+    threshold: f64,
+    #[structopt(flatten)]
+    output: OutputOpt,
+}
+
+fn compute_rips(cmd: ComputeRips) -> Result<(), failure::Error> {
+    let file = BufReader::new(File::open(&cmd.input)?);
+
+    let filt = match cmd.input_format.as_str() {
+        "lower-distance" => {
+            let matrix = reader::distance::read_lower_distance_matrix(file)?;
+            rips_filtration(&matrix, cmd.dim + 1, cmd.threshold)
+        },
+        "distance" => {
+            let matrix = reader::distance::read_full_distance_matrix(file)?;
+            rips_filtration(&matrix, cmd.dim + 1, cmd.threshold)
+        },
+        "sparse" => {
+            let matrix = reader::distance::read_sparse_distance_matrix(file)?;
+            rips_filtration(&matrix, cmd.dim + 1, cmd.threshold)
+        },
+        _ => {
+            let cloud = reader::distance::read_point_cloud(file)?;
+            rips_filtration(&cloud, cmd.dim + 1, cmd.threshold)
+        },
+    };
+
+    let records = pair_records(&filt.complex, Some(&filt.values), cycle_vertices(cmd.output.format))?
+        .into_iter()
+        .filter(|record| record.dim <= cmd.dim && !is_zero_length(record))
+        .collect::<Vec<_>>();
+    print_pairs(&records, cmd.output.format);
+
+    Ok(())
+}
+
 #[derive(Debug, StructOpt)]
 struct ComputeHomology {
     /// Input file path
     #[structopt(name = "INPUT", parse(from_os_str))]
     input: PathBuf,
+    /// Read only the maximal simplices and add all their faces
+    #[structopt(long = "facets")]
+    facets: bool,
+    /// Read the input as a mesh in the format (`off`, `obj` or `ply`)
+    #[structopt(
+        long = "mesh",
+        name = "MESH",
+        raw(possible_values = "&[\"off\", \"obj\", \"ply\"]"),
+        raw(conflicts_with = "\"facets\"")
+    )]
+    mesh: Option<MeshFormat>,
+    #[structopt(flatten)]
+    output: OutputOpt,
 }
 
-fn compute_homology(cmd: ComputeHomology) {
-    let file = File::open(cmd.input).unwrap();
+#[derive(Debug, StructOpt)]
+struct ComputeImage {
+    /// Input file path
+    #[structopt(name = "INPUT", parse(from_os_str))]
+    input: PathBuf,
+    /// Input format
+    #[structopt(
+        long = "input-format",
+        default_value = "npy",
+        raw(possible_values = "&[\"npy\", \"raw\"]")
+    )]
+    input_format: String,
+    /// Data type of the raw volume, e.g. `u1`, `i2`, `f4`, `uint8` or `float32`
+    #[structopt(long = "dtype", raw(required_if = "\"input_format\", \"raw\""))]
+    dtype: Option<DataType>,
+    /// Shape of the raw volume separated by commas, e.g. `512,512,512`
+    #[structopt(
+        long = "shape",
+        parse(try_from_str = "parse_shape"),
+        raw(required_if = "\"input_format\", \"raw\"")
+    )]
+    shape: Option<Vec<usize>>,
+    /// Byte order of the raw volume
+    #[structopt(
+        long = "endian",
+        default_value = "little",
+        raw(possible_values = "&[\"little\", \"big\"]")
+    )]
+    endian: Endianness,
+    /// Filtration of the cubical complex
+    #[structopt(
+        long = "filtration",
+        default_value = "voxel",
+        raw(possible_values = "&[\"voxel\", \"lower-star\"]")
+    )]
+    filtration: String,
+    #[structopt(flatten)]
+    output: OutputOpt,
+}
 
-    let comp = reader::simpcomp::read_simpcomp_text(BufReader::new(file)).unwrap();
+fn parse_shape(s: &str) -> Result<Vec<usize>, std::num::ParseIntError> {
+    s.split(',').map(|size| size.trim().parse()).collect()
+}
 
-    let reduce = Z2ColumnReduce::<Z2Chain<Z2VectorVec>>
-            ::from_complex_with(&comp, |index, chain| Z2Chain::new(index, chain)).unwrap();
+fn compute_image(cmd: ComputeImage) -> Result<(), failure::Error> {
+    let file = BufReader::new(File::open(&cmd.input)?);
 
-    let generators = Pair::new(&reduce, reduce.cycles())
-        .filter(|pair| pair.0.is_essential())
-        .map(|(_, chain)| {
-            chain.chain
-                .iter()
-                .map(|index| &comp.basis[*index])
-                .collect::<Vec<&Simplex>>()
-        })
+    let volume = match (cmd.input_format.as_str(), cmd.dtype, cmd.shape) {
+        ("raw", Some(dtype), Some(shape)) => {
+            reader::volume::read_raw(file, dtype, cmd.endian, &shape)?
+        },
+        _ => reader::volume::read_npy(file)?,
+    };
+
+    let filt = match cmd.filtration.as_str() {
+        "lower-star" => volume.lower_star_filtration()?,
+        _ => volume.voxel_filtration()?,
+    };
+
+    let records = matrix_records(&filt.boundary_matrix(), Some(&filt.values))
+        .into_iter()
+        .filter(|record| !is_zero_length(record))
+        .collect::<Vec<_>>();
+    print_pairs(&records, cmd.output.format);
+
+    Ok(())
+}
+
+#[derive(Debug, StructOpt)]
+struct ComputeBoundary {
+    /// Input file path
+    #[structopt(name = "INPUT", parse(from_os_str))]
+    input: PathBuf,
+    /// Input format
+    #[structopt(
+        long = "from",
+        default_value = "phat-ascii",
+        raw(possible_values = "&[\"phat-ascii\", \"phat-binary\", \"dipha\", \"complex\"]")
+    )]
+    from: String,
+    /// Format of the output file
+    #[structopt(
+        long = "to",
+        raw(
+            possible_values = "&[\"phat-ascii\", \"phat-binary\", \"phat-pairs-ascii\", \"phat-pairs-binary\", \"dipha\", \"dipha-diagram\"]"
+        ),
+        raw(requires = "\"output\"")
+    )]
+    to: Option<String>,
+    /// Output file path
+    #[structopt(long = "output", parse(from_os_str), raw(requires = "\"to\""))]
+    output: Option<PathBuf>,
+    #[structopt(flatten)]
+    printed: OutputOpt,
+}
+
+fn compute_boundary(cmd: ComputeBoundary) -> Result<(), failure::Error> {
+    let file = BufReader::new(File::open(&cmd.input)?);
+
+    let (matrix, values) = match cmd.from.as_str() {
+        "phat-binary" => (reader::phat::read_phat_binary(&mut { file })?, None),
+        "dipha" => {
+            let (matrix, values) = reader::dipha::read_dipha_boundary_matrix(&mut { file })?;
+            (matrix, Some(values))
+        },
+        "complex" => {
+            let comp = reader::simpcomp::read_simpcomp_text(file)?;
+            (BoundaryMatrix::from_complex(&comp)?, None)
+        },
+        _ => (reader::phat::read_phat_ascii(file)?, None),
+    };
+
+    if let (Some(to), Some(output)) = (cmd.to, cmd.output) {
+        let values = values.unwrap_or_else(|| (0..matrix.len()).map(|i| i as f64).collect());
+        let mut writer = BufWriter::new(File::create(output)?);
+        match to.as_str() {
+            "phat-ascii" => reader::phat::write_phat_ascii(&mut writer, &matrix)?,
+            "phat-binary" => reader::phat::write_phat_binary(&mut writer, &matrix)?,
+            "phat-pairs-ascii" => reader::phat::write_phat_pairs_ascii(&mut writer, &finite_pairs(&matrix))?,
+            "phat-pairs-binary" => reader::phat::write_phat_pairs_binary(&mut writer, &finite_pairs(&matrix))?,
+            "dipha" => reader::dipha::write_dipha_boundary_matrix(&mut writer, &matrix, &values)?,
+            _ => {
+                let diagram = matrix
+                    .pairs()
+                    .into_iter()
+                    .map(|(dim, Persistence(birth, death))| {
+                        (dim, Persistence(values[birth], death.map(|death| values[death])))
+                    })
+                    .collect::<Vec<_>>();
+                reader::dipha::write_dipha_diagram(&mut writer, &diagram)?
+            },
+        }
+        writer.flush()?;
+        return Ok(());
+    }
+
+    let records = matrix_records(&matrix, values.as_ref().map(|values| &values[..]));
+    print_pairs(&records, cmd.printed.format);
+
+    Ok(())
+}
+
+fn compute_homology(cmd: ComputeHomology) -> Result<(), failure::Error> {
+    let comp = read_complex(&cmd.input, cmd.facets, cmd.mesh)?;
+
+    let generators = pair_records(&comp, None, Some(simplex_vertices))?
+        .into_iter()
+        .filter(|record| record.indices.is_essential())
         .collect::<Vec<_>>();
 
-    let mut gen_dict = BTreeMap::<usize, Vec<Vec<&Simplex>>>::new();
+    let mut gen_dict = BTreeMap::<usize, Vec<Vec<Vec<usize>>>>::new();
 
     for generator in generators.into_iter() {
-        match gen_dict.get_mut(&generator[0].dimension()) {
+        let cycle = generator.cycle.unwrap();
+        match gen_dict.get_mut(&generator.dim) {
             Some(vec) => {
-                vec.push(generator);
+                vec.push(cycle);
             },
             None => {
-                gen_dict.insert(generator[0].dimension(), vec![generator]);
+                gen_dict.insert(generator.dim, vec![cycle]);
             },
         }
     }
 
-    for (dim, generators) in gen_dict.iter() {
-        println!("# dim {}", dim);
-        for gen in generators.iter() {
-            print!("[");
-            for simp in gen.iter() {
-                print!("{},", simp);
+    match cmd.output.format {
+        OutputFormat::Text => {
+            for (dim, generators) in gen_dict.iter() {
+                println!("# dim {}", dim);
+                for gen in generators.iter() {
+                    print!("[");
+                    for vertices in gen.iter() {
+                        print!("{},", Simplex::new(vertices.clone()));
+                    }
+                    println!("]");
+                }
             }
-            println!("]");
-        }
+        },
+        OutputFormat::Csv => {
+            println!("dim,cycle");
+            for (dim, generators) in gen_dict.iter() {
+                for gen in generators.iter() {
+                    println!("{},{}", dim, csv_cycle(gen));
+                }
+            }
+        },
+        OutputFormat::Json => {
+            let objects = gen_dict
+                .iter()
+                .flat_map(|(dim, generators)| {
+                    generators
+                        .iter()
+                        .map(move |gen| format!("{{\"dim\":{},\"cycle\":{}}}", dim, json_cycle(gen)))
+                })
+                .collect::<Vec<_>>();
+            println!("[{}]", objects.join(","));
+        },
     }
+
+    Ok(())
+}
+
+fn read_complex(input: &PathBuf, facets: bool, mesh: Option<MeshFormat>) -> Result<Complex<IndexedVec<Simplex>, Simplex>, failure::Error> {
+    let file = BufReader::new(File::open(input)?);
+
+    let comp = if let Some(format) = mesh {
+        reader::mesh::read_mesh(file, format)?.complex()
+    } else if facets {
+        reader::simpcomp::read_simpcomp_facets_text(file)?
+    } else {
+        reader::simpcomp::read_simpcomp_text(file)?
+    };
+
+    Ok(comp)
 }
 
 fn main() {
     let opt = Opt::from_args();
 
-    match opt.command {
+    let result = match opt.command {
         Command::Homology(cmd) => compute_homology(cmd),
         Command::Persistence(cmd) => compute_persistence(cmd),
+        Command::Rips(cmd) => compute_rips(cmd),
+        Command::Image(cmd) => compute_image(cmd),
+        Command::Boundary(cmd) => compute_boundary(cmd),
+    };
+
+    if let Err(e) = result {
+        eprintln!("error: {}", e);
+        std::process::exit(1);
     }
 }
@@ -0,0 +1,170 @@
+use crate::complex::Complex;
+use crate::distance::Distance;
+use crate::indexed_vec::IndexedVec;
+use crate::nerve::nerve;
+use crate::simplex::Simplex;
+
+/// Cover of a closed interval by overlapping intervals of equal length
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IntervalCover {
+    min: f64,
+    max: f64,
+    count: usize,
+    overlap: f64,
+}
+
+impl IntervalCover {
+    /// Creates a cover of `[min, max]` by `count` intervals.
+    ///
+    /// Consecutive intervals overlap by the fraction `overlap` of their length.
+    pub fn new(min: f64, max: f64, count: usize, overlap: f64) -> Self {
+        assert!(min <= max);
+        assert!(count > 0);
+        assert!((0.0..1.0).contains(&overlap));
+        IntervalCover {
+            min: min,
+            max: max,
+            count: count,
+            overlap: overlap,
+        }
+    }
+
+    /// Creates a cover of the range of the values.
+    pub fn of_values(values: &[f64], count: usize, overlap: f64) -> Self {
+        let min = values.iter().cloned().fold(f64::INFINITY, f64::min);
+        let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        if min > max {
+            Self::new(0.0, 0.0, count, overlap)
+        } else {
+            Self::new(min, max, count, overlap)
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Returns the `i`-th interval as the pair of its ends.
+    pub fn interval(&self, i: usize) -> (f64, f64) {
+        assert!(i < self.count);
+        let count = self.count as f64;
+        let length = (self.max - self.min) / (count - (count - 1.0) * self.overlap);
+        let lower = self.min + i as f64 * length * (1.0 - self.overlap);
+        if i + 1 == self.count {
+            (lower, self.max)
+        } else {
+            (lower, lower + length)
+        }
+    }
+}
+
+/// The result of the Mapper construction
+#[derive(Debug, Clone)]
+pub struct Mapper {
+    /// The nerve of the clusters
+    pub complex: Complex<IndexedVec<Simplex>, Simplex>,
+    /// The points of each cluster, i.e. of each vertex of `complex`
+    pub clusters: Vec<Vec<usize>>,
+}
+
+/// Builds the Mapper complex of the metric space.
+///
+/// The preimage of each interval of `cover` under the filter function, given by its values
+/// `filter` at the points, is clustered by single linkage: two points are in the same cluster
+/// if they are connected by a chain of points at distance at most `linkage`.
+/// The complex is the nerve of the clusters up to the dimension `max_dim`.
+pub fn mapper<D: Distance>(
+    space: &D,
+    filter: &[f64],
+    cover: &IntervalCover,
+    linkage: f64,
+    max_dim: usize,
+) -> Mapper {
+    assert_eq!(space.len(), filter.len());
+
+    let mut clusters = Vec::new();
+    for i in 0..cover.len() {
+        let (lower, upper) = cover.interval(i);
+        let preimage: Vec<usize> = (0..filter.len())
+            .filter(|p| lower <= filter[*p] && filter[*p] <= upper)
+            .collect();
+        clusters.extend(single_linkage(space, &preimage, linkage));
+    }
+
+    Mapper {
+        complex: nerve(&clusters, max_dim),
+        clusters: clusters,
+    }
+}
+
+/// Clusters the points by single linkage with the threshold.
+fn single_linkage<D: Distance>(space: &D, points: &[usize], threshold: f64) -> Vec<Vec<usize>> {
+    let mut parent: Vec<usize> = (0..points.len()).collect();
+    for i in 0..points.len() {
+        for j in i + 1..points.len() {
+            if space.distance(points[i], points[j]) <= threshold {
+                let (a, b) = (find(&mut parent, i), find(&mut parent, j));
+                parent[a.max(b)] = a.min(b);
+            }
+        }
+    }
+
+    let mut clusters: Vec<Vec<usize>> = Vec::new();
+    let mut cluster_of = vec![0; points.len()];
+    for i in 0..points.len() {
+        let root = find(&mut parent, i);
+        if root == i {
+            cluster_of[i] = clusters.len();
+            clusters.push(vec![points[i]]);
+        } else {
+            clusters[cluster_of[root]].push(points[i]);
+        }
+    }
+    clusters
+}
+
+/// Finds the root of the element in the union-find forest.
+fn find(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::distance::PointCloud;
+    use crate::mapper::{mapper, IntervalCover};
+    use crate::testing::{betti_numbers, circle_points};
+
+    #[test]
+    fn test_interval_cover() {
+        let cover = IntervalCover::new(0.0, 10.0, 4, 0.5);
+        assert_eq!(cover.interval(0), (0.0, 4.0));
+        assert_eq!(cover.interval(1), (2.0, 6.0));
+        assert_eq!(cover.interval(3), (6.0, 10.0));
+    }
+
+    #[test]
+    fn test_mapper_circle() {
+        let n = 60;
+        let points = circle_points(n);
+        let filter: Vec<f64> = points.iter().map(|p| p[0]).collect();
+        let space = PointCloud::new(points);
+        let cover = IntervalCover::of_values(&filter, 4, 0.3);
+        let mapper = mapper(&space, &filter, &cover, 0.2, 2);
+
+        // Both ends of the filter give one cluster and the others give two.
+        assert_eq!(mapper.clusters.len(), 6);
+        assert!(mapper.clusters.iter().map(|c| c.len()).sum::<usize>() > n);
+
+        assert_eq!(betti_numbers(&mapper.complex, 2), vec![1, 1, 0]);
+    }
+}
@@ -0,0 +1,110 @@
+use crate::complex::Complex;
+use crate::indexed_vec::IndexedVec;
+use crate::simplex::Simplex;
+use std::collections::BTreeSet;
+
+/// Builds the nerve of the cover.
+///
+/// The `i`-th element of `cover` is a set of points and becomes the vertex `i` of the nerve.
+/// A simplex is in the nerve if the elements of its vertices have a common point.
+/// The simplices of dimension greater than `max_dim` are not included.
+/// The simplices are ordered by dimension.
+pub fn nerve(cover: &[Vec<usize>], max_dim: usize) -> Complex<IndexedVec<Simplex>, Simplex> {
+    let sets: Vec<Vec<usize>> = cover
+        .iter()
+        .map(|set| {
+            let mut set = set.clone();
+            set.sort();
+            set.dedup();
+            set
+        })
+        .collect();
+
+    let mut simplices = BTreeSet::new();
+    for (i, set) in sets.iter().enumerate() {
+        if !set.is_empty() {
+            expand(&sets, vec![i], set, max_dim, &mut simplices);
+        }
+    }
+
+    let mut complex = Complex::new();
+    for (_, vertices) in simplices.into_iter() {
+        complex.push_unchecked(Simplex::new(vertices));
+    }
+    complex
+}
+
+/// Adds `simplex` and its cofaces with larger vertices, where `common` is the intersection of
+/// the elements of the vertices of `simplex`.
+fn expand(
+    sets: &[Vec<usize>],
+    simplex: Vec<usize>,
+    common: &[usize],
+    max_dim: usize,
+    simplices: &mut BTreeSet<(usize, Vec<usize>)>,
+) {
+    if simplex.len() <= max_dim {
+        let last = *simplex.last().unwrap();
+        for j in last + 1..sets.len() {
+            let common = intersection(common, &sets[j]);
+            if !common.is_empty() {
+                let mut coface = simplex.clone();
+                coface.push(j);
+                expand(sets, coface, &common, max_dim, simplices);
+            }
+        }
+    }
+    simplices.insert((simplex.len() - 1, simplex));
+}
+
+/// Returns the intersection of two sorted sets.
+fn intersection(a: &[usize], b: &[usize]) -> Vec<usize> {
+    let mut result = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i] < b[j] {
+            i += 1;
+        } else if a[i] > b[j] {
+            j += 1;
+        } else {
+            result.push(a[i]);
+            i += 1;
+            j += 1;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::nerve::nerve;
+    use crate::simplex;
+    use crate::simplex::Simplex;
+    use crate::traits::*;
+
+    #[test]
+    fn test_nerve() {
+        // The three arcs of a circle.
+        let cover = vec![vec![0, 1], vec![1, 2], vec![2, 0]];
+        let complex = nerve(&cover, 2);
+        let simplices: Vec<Simplex> = complex.basis.iter().map(|(_, s)| s.clone()).collect();
+        assert_eq!(
+            simplices,
+            vec![
+                simplex![0],
+                simplex![1],
+                simplex![2],
+                simplex![0, 1],
+                simplex![0, 2],
+                simplex![1, 2],
+            ]
+        );
+
+        // The sets with a common point span a simplex.
+        let cover = vec![vec![0, 1], vec![1, 2], vec![1, 3], vec![], vec![4]];
+        let complex = nerve(&cover, 1);
+        assert_eq!(complex.basis.len(), 4 + 3);
+        let complex = nerve(&cover, 2);
+        assert_eq!(complex.basis.len(), 4 + 3 + 1);
+    }
+}
@@ -0,0 +1,410 @@
+use crate::complex::{Complex, ComplexError};
+use crate::filtration::{compare_values, Filtration};
+use crate::indexed_vec::IndexedVec;
+use crate::simplex::Simplex;
+use crate::traits::*;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Builds the barycentric subdivision of the complex.
+///
+/// The vertices of the subdivision are the simplices of `complex`, labeled by their positions
+/// from the start of the basis, and its simplices are the chains of faces.
+/// The simplices are ordered by dimension.
+/// It returns an error if a face of a simplex does not precede it in `complex`
+/// or if a simplex has too many vertices to enumerate its faces.
+pub fn barycentric_subdivision<V>(
+    complex: &Complex<V, Simplex>,
+) -> Result<Complex<IndexedVec<Simplex>, Simplex>, ComplexError>
+where
+    V: IndexedSet<Simplex> + for<'a> IndexedSetIters<'a, Simplex>,
+{
+    let simplices = vertex_lists(complex);
+    let positions: BTreeMap<&[usize], usize> = simplices
+        .iter()
+        .enumerate()
+        .map(|(i, vertices)| (&vertices[..], i))
+        .collect();
+
+    // The chains of faces whose last element is each simplex.
+    let mut chains: Vec<Vec<Vec<usize>>> = Vec::with_capacity(simplices.len());
+    let mut result = BTreeSet::new();
+    for (i, vertices) in simplices.iter().enumerate() {
+        let mut ending = vec![vec![i]];
+        let faces = proper_faces(vertices).ok_or(ComplexError::TooManyVertices(vertices.len()))?;
+        for face in faces {
+            let j = match positions.get(&face[..]) {
+                Some(&j) if j < i => j,
+                _ => return Err(ComplexError::ComplexIsNotFiltered),
+            };
+            for chain in chains[j].iter() {
+                let mut chain = chain.clone();
+                chain.push(i);
+                ending.push(chain);
+            }
+        }
+        for chain in ending.iter() {
+            result.insert((chain.len() - 1, chain.clone()));
+        }
+        chains.push(ending);
+    }
+
+    let mut subdivision = Complex::new();
+    for (_, vertices) in result.into_iter() {
+        subdivision.push_unchecked(Simplex::new(vertices));
+    }
+    Ok(subdivision)
+}
+
+/// Builds the cone over the complex.
+///
+/// The apex is the vertex following the largest vertex of `complex`.
+/// The simplices of `complex` keep their order and are followed by the apex and the cones
+/// over them, so a filtered complex gives a filtered cone.
+pub fn cone<V>(complex: &Complex<V, Simplex>) -> Complex<IndexedVec<Simplex>, Simplex>
+where
+    V: IndexedSet<Simplex> + for<'a> IndexedSetIters<'a, Simplex>,
+{
+    let mut apex = Complex::<IndexedVec<Simplex>, Simplex>::new();
+    apex.push_unchecked(Simplex::new(vec![0]));
+    join(complex, &apex)
+}
+
+/// Builds the suspension of the complex.
+///
+/// The two apices are the vertices following the largest vertex of `complex`.
+/// The simplices are ordered as in `join`.
+pub fn suspension<V>(complex: &Complex<V, Simplex>) -> Complex<IndexedVec<Simplex>, Simplex>
+where
+    V: IndexedSet<Simplex> + for<'a> IndexedSetIters<'a, Simplex>,
+{
+    let mut sphere = Complex::<IndexedVec<Simplex>, Simplex>::new();
+    sphere.push_unchecked(Simplex::new(vec![0]));
+    sphere.push_unchecked(Simplex::new(vec![1]));
+    join(complex, &sphere)
+}
+
+/// Builds the join of two complexes.
+///
+/// The vertices of `other` are shifted so that they follow the largest vertex of `complex`.
+/// The simplices of `complex` come first in their order. Then, for each simplex `b` of `other`
+/// in its order, `b` is followed by the joins of `b` with the simplices of `complex`.
+/// Hence the join of filtered complexes is filtered.
+pub fn join<V, W>(
+    complex: &Complex<V, Simplex>,
+    other: &Complex<W, Simplex>,
+) -> Complex<IndexedVec<Simplex>, Simplex>
+where
+    V: IndexedSet<Simplex> + for<'a> IndexedSetIters<'a, Simplex>,
+    W: IndexedSet<Simplex> + for<'a> IndexedSetIters<'a, Simplex>,
+{
+    let first = vertex_lists(complex);
+    let second = shifted_vertex_lists(other, vertex_count(&first));
+
+    let mut result = first.clone();
+    for b in second.iter() {
+        result.push(b.clone());
+        for a in first.iter() {
+            result.push(a.iter().chain(b.iter()).cloned().collect());
+        }
+    }
+
+    from_vertex_lists(result)
+}
+
+/// Builds the disjoint union of two complexes.
+///
+/// The vertices of `other` are shifted so that they follow the largest vertex of `complex`.
+/// The simplices of `complex` are followed by those of `other`, both in their order.
+pub fn disjoint_union<V, W>(
+    complex: &Complex<V, Simplex>,
+    other: &Complex<W, Simplex>,
+) -> Complex<IndexedVec<Simplex>, Simplex>
+where
+    V: IndexedSet<Simplex> + for<'a> IndexedSetIters<'a, Simplex>,
+    W: IndexedSet<Simplex> + for<'a> IndexedSetIters<'a, Simplex>,
+{
+    let first = vertex_lists(complex);
+    let second = shifted_vertex_lists(other, vertex_count(&first));
+
+    from_vertex_lists(first.into_iter().chain(second).collect())
+}
+
+/// Builds the triangulated product of two complexes.
+///
+/// The vertex `(u, v)` of the product is labeled `u * n + v`, where `n` is the largest vertex
+/// of `other` plus one. The product of two simplices is triangulated by the staircase
+/// simplices, i.e. the chains of vertices increasing in both coordinates, so the vertices
+/// of both complexes are ordered by their labels. The simplices are ordered by dimension.
+pub fn product<V, W>(
+    complex: &Complex<V, Simplex>,
+    other: &Complex<W, Simplex>,
+) -> Complex<IndexedVec<Simplex>, Simplex>
+where
+    V: IndexedSet<Simplex> + for<'a> IndexedSetIters<'a, Simplex>,
+    W: IndexedSet<Simplex> + for<'a> IndexedSetIters<'a, Simplex>,
+{
+    let result: BTreeSet<(usize, Vec<usize>)> =
+        product_vertex_lists(&vertex_lists(complex), &vertex_lists(other))
+            .into_iter()
+            .map(|(_, _, vertices)| (vertices.len() - 1, vertices))
+            .collect();
+    from_vertex_lists(result.into_iter().map(|(_, vertices)| vertices).collect())
+}
+
+/// Builds the triangulated product of two filtrations.
+///
+/// The filtration value of a simplex of the product is the maximum of the values of its
+/// projections to the factors. See `product` for the labels of the vertices.
+pub fn product_filtration<T>(
+    filt: &Filtration<Simplex, T>,
+    other: &Filtration<Simplex, T>,
+) -> Filtration<Simplex, T>
+where
+    T: PartialOrd + Clone,
+{
+    let elems = product_vertex_lists(&vertex_lists(&filt.complex), &vertex_lists(&other.complex))
+        .into_iter()
+        .map(|(i, j, vertices)| {
+            let (a, b) = (&filt.values[i], &other.values[j]);
+            let value = match compare_values(a, b) {
+                Ordering::Less => b.clone(),
+                _ => a.clone(),
+            };
+            (Simplex::new(vertices), value)
+        })
+        .collect();
+    Filtration::from_unsorted(elems)
+}
+
+/// Returns the staircase simplices of the products of all pairs of simplices.
+///
+/// Each staircase simplex is given with the positions of the simplices of which it is
+/// in the product, and projects onto both of them.
+fn product_vertex_lists(
+    first: &[Vec<usize>],
+    second: &[Vec<usize>],
+) -> Vec<(usize, usize, Vec<usize>)> {
+    let n = vertex_count(second);
+    let mut result = Vec::new();
+    for (i, a) in first.iter().enumerate() {
+        for (j, b) in second.iter().enumerate() {
+            let mut stack = vec![(0, 0, vec![a[0] * n + b[0]])];
+            while let Some((p, q, vertices)) = stack.pop() {
+                if p + 1 == a.len() && q + 1 == b.len() {
+                    result.push((i, j, vertices));
+                    continue;
+                }
+                for (dp, dq) in [(1, 0), (0, 1), (1, 1)].iter() {
+                    let (p, q) = (p + dp, q + dq);
+                    if p < a.len() && q < b.len() {
+                        let mut vertices = vertices.clone();
+                        vertices.push(a[p] * n + b[q]);
+                        stack.push((p, q, vertices));
+                    }
+                }
+            }
+        }
+    }
+    result
+}
+
+/// Returns the vertices of the simplices of the complex in the order of the basis.
+fn vertex_lists<V>(complex: &Complex<V, Simplex>) -> Vec<Vec<usize>>
+where
+    V: IndexedSet<Simplex> + for<'a> IndexedSetIters<'a, Simplex>,
+{
+    complex
+        .basis
+        .iter()
+        .map(|(_, simplex)| simplex.vertices().cloned().collect())
+        .collect()
+}
+
+fn shifted_vertex_lists<V>(complex: &Complex<V, Simplex>, shift: usize) -> Vec<Vec<usize>>
+where
+    V: IndexedSet<Simplex> + for<'a> IndexedSetIters<'a, Simplex>,
+{
+    vertex_lists(complex)
+        .into_iter()
+        .map(|vertices| vertices.into_iter().map(|v| v + shift).collect())
+        .collect()
+}
+
+/// Returns the number of the vertices, i.e. the largest vertex plus one.
+fn vertex_count(simplices: &[Vec<usize>]) -> usize {
+    simplices
+        .iter()
+        .filter_map(|vertices| vertices.last())
+        .map(|v| v + 1)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Returns the non-empty proper faces of the simplex.
+///
+/// It returns `None` if the number of the faces overflows `usize`.
+fn proper_faces(vertices: &[usize]) -> Option<Vec<Vec<usize>>> {
+    let n = vertices.len();
+    let count = 1usize.checked_shl(n as u32)?;
+    let faces = (1..count - 1)
+        .map(|mask| {
+            (0..n)
+                .filter(|i| mask & (1 << i) != 0)
+                .map(|i| vertices[i])
+                .collect()
+        })
+        .collect();
+    Some(faces)
+}
+
+fn from_vertex_lists(simplices: Vec<Vec<usize>>) -> Complex<IndexedVec<Simplex>, Simplex> {
+    let mut complex = Complex::new();
+    for vertices in simplices.into_iter() {
+        complex.push_unchecked(Simplex::new(vertices));
+    }
+    complex
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::complex::{Complex, ComplexError};
+    use crate::filtration::Filtration;
+    use crate::indexed_vec::IndexedVec;
+    use crate::operations::{
+        barycentric_subdivision, cone, disjoint_union, join, product, product_filtration,
+        suspension,
+    };
+    use crate::simplex;
+    use crate::simplex::Simplex;
+    use crate::testing::{betti_numbers, triangle_boundary as circle};
+    use crate::traits::*;
+
+    fn complex_of(simplices: Vec<Simplex>) -> Complex<IndexedVec<Simplex>, Simplex> {
+        let mut complex = Complex::new();
+        for simplex in simplices.into_iter() {
+            complex.push(simplex).unwrap();
+        }
+        complex
+    }
+
+    fn count_by_dimension(complex: &Complex<IndexedVec<Simplex>, Simplex>) -> Vec<usize> {
+        let mut counts = Vec::new();
+        for (_, simplex) in complex.basis.iter() {
+            if counts.len() <= simplex.dimension() {
+                counts.resize(simplex.dimension() + 1, 0);
+            }
+            counts[simplex.dimension()] += 1;
+        }
+        counts
+    }
+
+    #[test]
+    fn test_barycentric_subdivision() {
+        let subdivision = barycentric_subdivision(&circle()).unwrap();
+        assert_eq!(count_by_dimension(&subdivision), vec![6, 6]);
+        assert_eq!(betti_numbers(&subdivision, 1), vec![1, 1]);
+
+        let mut triangle = circle();
+        triangle.push(simplex![0, 1, 2]).unwrap();
+        let subdivision = barycentric_subdivision(&triangle).unwrap();
+        assert_eq!(count_by_dimension(&subdivision), vec![7, 12, 6]);
+        assert_eq!(betti_numbers(&subdivision, 2), vec![1, 0, 0]);
+
+        let mut open = Complex::<IndexedVec<Simplex>, Simplex>::new();
+        open.push_unchecked(simplex![0, 1]);
+        open.push_unchecked(simplex![0]);
+        open.push_unchecked(simplex![1]);
+        assert!(barycentric_subdivision(&open).is_err());
+
+        let mut wide = Complex::<IndexedVec<Simplex>, Simplex>::new();
+        wide.push_unchecked(Simplex::new((0..64).collect()));
+        match barycentric_subdivision(&wide) {
+            Err(ComplexError::TooManyVertices(64)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_cone_and_suspension() {
+        let cone = cone(&circle());
+        assert_eq!(count_by_dimension(&cone), vec![4, 6, 3]);
+        assert_eq!(betti_numbers(&cone, 2), vec![1, 0, 0]);
+        let simplices: Vec<Simplex> = cone.basis.iter().map(|(_, s)| s.clone()).collect();
+        let base: Vec<Simplex> = circle().basis.iter().map(|(_, s)| s.clone()).collect();
+        assert_eq!(&simplices[..6], &base[..]);
+        assert_eq!(
+            &simplices[6..],
+            &[
+                simplex![3],
+                simplex![0, 3],
+                simplex![1, 3],
+                simplex![2, 3],
+                simplex![0, 1, 3],
+                simplex![1, 2, 3],
+                simplex![0, 2, 3],
+            ]
+        );
+
+        let sphere = suspension(&circle());
+        assert_eq!(count_by_dimension(&sphere), vec![5, 9, 6]);
+        assert_eq!(betti_numbers(&sphere, 2), vec![1, 0, 1]);
+    }
+
+    #[test]
+    fn test_join_and_disjoint_union() {
+        let points = complex_of(vec![simplex![0], simplex![1]]);
+        let square = join(&points, &points);
+        assert_eq!(count_by_dimension(&square), vec![4, 4]);
+        assert_eq!(betti_numbers(&square, 1), vec![1, 1]);
+
+        let union = disjoint_union(&circle(), &square);
+        assert_eq!(count_by_dimension(&union), vec![7, 7]);
+        assert_eq!(betti_numbers(&union, 1), vec![2, 2]);
+        assert!(union
+            .basis
+            .iter()
+            .any(|(_, simplex)| simplex == &simplex![3, 5]));
+    }
+
+    #[test]
+    fn test_product() {
+        let torus = product(&circle(), &circle());
+        assert_eq!(count_by_dimension(&torus), vec![9, 27, 18]);
+        assert_eq!(betti_numbers(&torus, 2), vec![1, 2, 1]);
+
+        let torus = product(&torus, &circle());
+        assert_eq!(count_by_dimension(&torus), vec![27, 189, 324, 162]);
+        assert_eq!(betti_numbers(&torus, 3), vec![1, 3, 3, 1]);
+    }
+
+    #[test]
+    fn test_product_filtration() {
+        let mut interval = Filtration::new();
+        interval.push(simplex![0], 0.0).unwrap();
+        interval.push(simplex![1], 1.0).unwrap();
+        interval.push(simplex![0, 1], 2.0).unwrap();
+
+        let square = product_filtration(&interval, &interval);
+        assert_eq!(square.len(), 4 + 5 + 2);
+        let values: Vec<(Simplex, f64)> = square
+            .complex
+            .basis
+            .iter()
+            .map(|(index, s)| (s.clone(), square.values[index]))
+            .collect();
+        assert_eq!(values[0], (simplex![0], 0.0));
+        assert!(values.contains(&(simplex![3], 1.0)));
+        assert!(values.contains(&(simplex![1, 3], 2.0)));
+        assert!(values.contains(&(simplex![0, 1, 3], 2.0)));
+
+        let diagram = square.diagram().unwrap();
+        assert_eq!(
+            diagram
+                .iter()
+                .filter(|(_, pers)| pers.is_essential())
+                .count(),
+            1
+        );
+    }
+}
@@ -0,0 +1,387 @@
+use crate::bifiltration::{BifilteredComplex, Bigrade};
+use crate::filtration::compare_values;
+use crate::traits::*;
+use crate::z2reduce::Z2ColumnReduce;
+use crate::z2vector::{Z2Chain, Z2Vector, Z2VectorIter, Z2VectorVec};
+use failure::Fail;
+use std::io::Write;
+
+#[derive(Debug, Fail)]
+pub enum PresentationError {
+    #[fail(display = "element has more than one grade")]
+    MultiCriticalElement,
+    #[fail(display = "boundary at {} is not spanned by the cycles", _0)]
+    UnspannedBoundary(Bigrade),
+}
+
+/// Presentation of a 2-parameter persistence module
+///
+/// The module is generated by `generators` with their bigrades.
+/// A relation is a pair of its bigrade and the list of the generators whose sum vanishes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Presentation {
+    pub generators: Vec<Bigrade>,
+    pub relations: Vec<(Bigrade, Vec<usize>)>,
+}
+
+impl Presentation {
+    /// Computes a minimal presentation of the `dim`-th homology module of `bifilt`.
+    ///
+    /// Every element of `bifilt` must have exactly one grade.
+    pub fn minimal<G>(
+        bifilt: &BifilteredComplex<G>,
+        dim: usize,
+    ) -> Result<Presentation, failure::Error>
+    where
+        G: 'static + Clone + PartialEq + ChainGenerator + for<'a> ChainGeneratorBoundary<'a, G>,
+    {
+        let mut grades = Vec::with_capacity(bifilt.len());
+        for g in bifilt.grades.iter() {
+            if g.len() != 1 {
+                return Err(PresentationError::MultiCriticalElement.into());
+            }
+            grades.push(g[0]);
+        }
+
+        let mut boundaries = Vec::with_capacity(bifilt.len());
+        for result in bifilt.complex.boundaries::<Z2VectorVec>() {
+            let (_, image) = result?;
+            boundaries.push(image);
+        }
+
+        let cells: Vec<usize> = bifilt
+            .complex
+            .basis
+            .iter()
+            .filter(|(_, gen)| gen.dimension() == dim)
+            .map(|(index, _)| index)
+            .collect();
+        let mut cofaces: Vec<usize> = bifilt
+            .complex
+            .basis
+            .iter()
+            .filter(|(_, gen)| gen.dimension() == dim + 1)
+            .map(|(index, _)| index)
+            .collect();
+        cofaces.sort_by(|a, b| compare_lex(&grades[*a], &grades[*b]));
+
+        let cycles = kernel_generators(&cells, &grades, &boundaries);
+        let boundaries = image_generators(&cofaces, &grades, &boundaries);
+
+        // Express the boundaries in terms of the generators of the cycles.
+        let mut relations = Vec::with_capacity(boundaries.len());
+        for (grade, image) in boundaries.into_iter() {
+            let mut reduce = Z2ColumnReduce::<Z2Chain<Z2VectorVec>>::new(0);
+            for (index, (g, cycle)) in cycles.iter().enumerate() {
+                if g <= &grade {
+                    reduce.push(Z2Chain::new(index, cycle.clone()));
+                }
+            }
+            let mut relation = Z2Chain {
+                chain: Z2VectorVec::new(),
+                image: image,
+            };
+            reduce.reduce(&mut relation);
+            if !relation.is_cycle() {
+                return Err(PresentationError::UnspannedBoundary(grade).into());
+            }
+            relations.push((grade, relation.chain));
+        }
+
+        let generators: Vec<Bigrade> = cycles.into_iter().map(|(g, _)| g).collect();
+
+        Ok(minimize(generators, relations))
+    }
+
+    /// Writes the presentation in the FIRep format of RIVET.
+    ///
+    /// The relations are written as the columns of the matrix from F2 to F1,
+    /// and the generators are written as the zero columns from F1 to F0.
+    pub fn write_firep<W: Write>(
+        &self,
+        writer: &mut W,
+        xlabel: &str,
+        ylabel: &str,
+    ) -> std::io::Result<()> {
+        writeln!(writer, "--datatype firep")?;
+        writeln!(writer, "--xlabel {}", xlabel)?;
+        writeln!(writer, "--ylabel {}", ylabel)?;
+        writeln!(writer)?;
+        writeln!(
+            writer,
+            "{} {} 0",
+            self.relations.len(),
+            self.generators.len()
+        )?;
+        for (grade, relation) in self.relations.iter() {
+            write!(writer, "{} {} ;", grade.0, grade.1)?;
+            for index in relation.iter() {
+                write!(writer, " {}", index)?;
+            }
+            writeln!(writer)?;
+        }
+        for grade in self.generators.iter() {
+            writeln!(writer, "{} {} ;", grade.0, grade.1)?;
+        }
+        Ok(())
+    }
+}
+
+/// Compares bigrades in the lexicographic order, which is a linear extension of the product order.
+fn compare_lex(a: &Bigrade, b: &Bigrade) -> std::cmp::Ordering {
+    compare_values(&a.0, &b.0).then_with(|| compare_values(&a.1, &b.1))
+}
+
+/// Returns the sorted distinct values.
+fn distinct_values<I: Iterator<Item = f64>>(iter: I) -> Vec<f64> {
+    let mut values: Vec<f64> = iter.collect();
+    values.sort_by(compare_values);
+    values.dedup();
+    values
+}
+
+/// Computes a minimal set of generators of the kernel of the boundary map on `cells`.
+///
+/// The generators appear at the joins of the grades of `cells`,
+/// so it suffices to look at the grid spanned by their coordinates.
+/// At each grid point the cycles which are not spanned by the generators found at lower
+/// grid points become new generators.
+fn kernel_generators(
+    cells: &[usize],
+    grades: &[Bigrade],
+    boundaries: &[Z2VectorVec],
+) -> Vec<(Bigrade, Z2VectorVec)> {
+    let xs = distinct_values(cells.iter().map(|c| grades[*c].0));
+    let ys = distinct_values(cells.iter().map(|c| grades[*c].1));
+
+    let mut generators: Vec<(Bigrade, Z2VectorVec)> = Vec::new();
+    for x in xs.iter() {
+        for y in ys.iter() {
+            let point = Bigrade(*x, *y);
+
+            let mut reduce = Z2ColumnReduce::<Z2Chain<Z2VectorVec>>::new(0);
+            for c in cells.iter().filter(|c| grades[**c] <= point) {
+                reduce.push(Z2Chain::new(*c, boundaries[*c].clone()));
+            }
+
+            let mut span = Z2ColumnReduce::<Z2VectorVec>::new(0);
+            for (_, cycle) in generators.iter().filter(|(g, _)| *g <= point) {
+                span.push(cycle.clone());
+            }
+
+            for (_, cycle) in reduce.cycles() {
+                let mut residue = cycle.chain.clone();
+                span.reduce(&mut residue);
+                if !residue.is_cycle() {
+                    span.push(cycle.chain.clone());
+                    generators.push((point, cycle.chain.clone()));
+                }
+            }
+        }
+    }
+    generators
+}
+
+/// Computes a minimal set of generators of the image of the boundary map on `cofaces`.
+///
+/// The elements of `cofaces` must be sorted in the lexicographic order of their grades.
+fn image_generators(
+    cofaces: &[usize],
+    grades: &[Bigrade],
+    boundaries: &[Z2VectorVec],
+) -> Vec<(Bigrade, Z2VectorVec)> {
+    let mut generators: Vec<(Bigrade, Z2VectorVec)> = Vec::new();
+    for c in cofaces.iter() {
+        let grade = grades[*c];
+
+        let mut span = Z2ColumnReduce::<Z2VectorVec>::new(0);
+        for (_, image) in generators.iter().filter(|(g, _)| *g <= grade) {
+            span.push(image.clone());
+        }
+
+        let mut residue = boundaries[*c].clone();
+        span.reduce(&mut residue);
+        if !residue.is_cycle() {
+            generators.push((grade, boundaries[*c].clone()));
+        }
+    }
+    generators
+}
+
+/// Removes the pairs of a generator and a relation with the same grade.
+///
+/// Such a relation identifies the generator with a combination of the others,
+/// so the generator is eliminated from the other relations and both are removed.
+fn minimize(generators: Vec<Bigrade>, relations: Vec<(Bigrade, Z2VectorVec)>) -> Presentation {
+    let mut removed = vec![false; generators.len()];
+    let mut relations: Vec<Option<(Bigrade, Z2VectorVec)>> =
+        relations.into_iter().map(Some).collect();
+
+    loop {
+        let pivot = relations.iter().enumerate().find_map(|(i, rel)| {
+            rel.as_ref().and_then(|(grade, rel)| {
+                rel.iter()
+                    .find(|gen| generators[**gen] == *grade)
+                    .map(|gen| (i, *gen))
+            })
+        });
+
+        let (i, gen) = match pivot {
+            Some(pivot) => pivot,
+            None => break,
+        };
+
+        let (_, pivot_rel) = relations[i].take().unwrap();
+        removed[gen] = true;
+        for rel in relations.iter_mut() {
+            let is_zero = match rel {
+                Some((_, rel)) => {
+                    if rel.iter().any(|g| *g == gen) {
+                        rel.add_assign(&pivot_rel);
+                    }
+                    rel.lowest().is_none()
+                }
+                None => false,
+            };
+            if is_zero {
+                *rel = None;
+            }
+        }
+    }
+
+    let mut new_index = vec![0; generators.len()];
+    let mut new_generators = Vec::new();
+    for (index, grade) in generators.into_iter().enumerate() {
+        if !removed[index] {
+            new_index[index] = new_generators.len();
+            new_generators.push(grade);
+        }
+    }
+
+    let new_relations = relations
+        .into_iter()
+        .flatten()
+        .map(|(grade, rel)| {
+            let mut rel: Vec<usize> = rel.iter().map(|g| new_index[*g]).collect();
+            rel.sort();
+            (grade, rel)
+        })
+        .collect();
+
+    Presentation {
+        generators: new_generators,
+        relations: new_relations,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bifiltration::{BifilteredComplex, Bigrade};
+    use crate::presentation::{Presentation, PresentationError};
+    use crate::simplex;
+
+    fn firep(pres: &Presentation) -> String {
+        let mut buf = Vec::new();
+        pres.write_firep(&mut buf, "x", "y").unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    #[test]
+    fn test_two_points_merging() {
+        let mut bifilt = BifilteredComplex::new();
+        bifilt.push(simplex![0], vec![Bigrade(0.0, 0.0)]).unwrap();
+        bifilt.push(simplex![1], vec![Bigrade(1.0, 0.0)]).unwrap();
+        bifilt.push(simplex![0, 1], vec![Bigrade(1.0, 1.0)]).unwrap();
+
+        let pres = Presentation::minimal(&bifilt, 0).unwrap();
+        assert_eq!(pres.generators.len(), 2);
+        assert_eq!(pres.relations.len(), 1);
+        assert_eq!(
+            firep(&pres),
+            "--datatype firep\n--xlabel x\n--ylabel y\n\n1 2 0\n1 1 ; 0 1\n0 0 ;\n1 0 ;\n"
+        );
+
+        let pres = Presentation::minimal(&bifilt, 1).unwrap();
+        assert_eq!(pres.generators.len(), 0);
+        assert_eq!(pres.relations.len(), 0);
+    }
+
+    #[test]
+    fn test_circle_filled() {
+        let mut bifilt = BifilteredComplex::new();
+        let zero = Bigrade(0.0, 0.0);
+        bifilt.push(simplex![0], vec![zero]).unwrap();
+        bifilt.push(simplex![1], vec![zero]).unwrap();
+        bifilt.push(simplex![2], vec![zero]).unwrap();
+        bifilt.push(simplex![0, 1], vec![zero]).unwrap();
+        bifilt.push(simplex![1, 2], vec![zero]).unwrap();
+        bifilt.push(simplex![0, 2], vec![Bigrade(0.0, 1.0)]).unwrap();
+        bifilt.push(simplex![0, 1, 2], vec![Bigrade(2.0, 2.0)]).unwrap();
+
+        let pres = Presentation::minimal(&bifilt, 1).unwrap();
+        assert_eq!(pres.generators, vec![Bigrade(0.0, 1.0)]);
+        assert_eq!(pres.relations, vec![(Bigrade(2.0, 2.0), vec![0])]);
+        assert_eq!(
+            firep(&pres),
+            "--datatype firep\n--xlabel x\n--ylabel y\n\n1 1 0\n2 2 ; 0\n0 1 ;\n"
+        );
+
+        let pres = Presentation::minimal(&bifilt, 0).unwrap();
+        assert_eq!(pres.generators, vec![zero]);
+        assert_eq!(pres.relations.len(), 0);
+    }
+
+    #[test]
+    fn test_multi_critical_element() {
+        let mut bifilt = BifilteredComplex::new();
+        bifilt
+            .push(simplex![0], vec![Bigrade(0.0, 1.0), Bigrade(1.0, 0.0)])
+            .unwrap();
+        match Presentation::minimal(&bifilt, 0).map_err(|e| e.downcast::<PresentationError>()) {
+            Err(Ok(PresentationError::MultiCriticalElement)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_minimal_presentation() {
+        let mut bifilt = BifilteredComplex::new();
+        let zero = Bigrade(0.0, 0.0);
+        let cone = Bigrade(3.0, 1.0);
+        bifilt.push(simplex![0], vec![zero]).unwrap();
+        bifilt.push(simplex![1], vec![zero]).unwrap();
+        bifilt.push(simplex![2], vec![zero]).unwrap();
+        bifilt.push(simplex![0, 1], vec![zero]).unwrap();
+        bifilt.push(simplex![1, 2], vec![zero]).unwrap();
+        bifilt.push(simplex![0, 2], vec![Bigrade(1.0, 0.0)]).unwrap();
+        bifilt.push(simplex![0, 1, 2], vec![Bigrade(1.0, 2.0)]).unwrap();
+        bifilt.push(simplex![3], vec![cone]).unwrap();
+        bifilt.push(simplex![0, 3], vec![cone]).unwrap();
+        bifilt.push(simplex![1, 3], vec![cone]).unwrap();
+        bifilt.push(simplex![2, 3], vec![cone]).unwrap();
+        bifilt.push(simplex![0, 1, 3], vec![cone]).unwrap();
+        bifilt.push(simplex![1, 2, 3], vec![cone]).unwrap();
+        bifilt.push(simplex![0, 2, 3], vec![cone]).unwrap();
+
+        let pres = Presentation::minimal(&bifilt, 1).unwrap();
+        assert_eq!(pres.generators, vec![Bigrade(1.0, 0.0)]);
+        assert_eq!(
+            pres.relations,
+            vec![(Bigrade(1.0, 2.0), vec![0]), (Bigrade(3.0, 1.0), vec![0])]
+        );
+
+        let pres = Presentation::minimal(&bifilt, 0).unwrap();
+        assert_eq!(pres.generators, vec![zero]);
+        assert_eq!(pres.relations, vec![]);
+
+        let mut firep = Vec::new();
+        Presentation::minimal(&bifilt, 1)
+            .unwrap()
+            .write_firep(&mut firep, "x", "y")
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(firep).unwrap(),
+            "--datatype firep\n--xlabel x\n--ylabel y\n\n2 1 0\n1 2 ; 0\n3 1 ; 0\n1 0 ;\n"
+        );
+    }
+}
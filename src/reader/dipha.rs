@@ -0,0 +1,285 @@
+use crate::boundary_matrix::BoundaryMatrix;
+use crate::reader::phat::{read_i64, write_i64};
+use crate::Persistence;
+use failure::Fail;
+use std::io::{Read, Write};
+
+/// The magic number of the DIPHA files
+const DIPHA_MAGIC: i64 = 8_067_171_840;
+/// The file type of a weighted boundary matrix
+const WEIGHTED_BOUNDARY_MATRIX: i64 = 0;
+/// The file type of a persistence diagram
+const PERSISTENCE_DIAGRAM: i64 = 2;
+
+/// An error in reading a file in the DIPHA format
+#[derive(Debug, Fail)]
+pub enum DiphaError {
+    #[fail(display = "I/O error: {}", _0)]
+    Io(#[cause] std::io::Error),
+    #[fail(display = "not a DIPHA file")]
+    NotDipha,
+    #[fail(display = "unexpected file type {}", _0)]
+    UnexpectedFileType(i64),
+    #[fail(display = "coboundary matrices are not supported")]
+    Coboundary,
+    #[fail(display = "invalid {}: {}", _0, _1)]
+    InvalidValue(&'static str, i64),
+    #[fail(
+        display = "column {}: row index {} is not less than the column index",
+        column, row
+    )]
+    NotTriangular { column: usize, row: usize },
+}
+
+impl From<std::io::Error> for DiphaError {
+    fn from(e: std::io::Error) -> Self {
+        DiphaError::Io(e)
+    }
+}
+
+/// Reads a weighted boundary matrix in the DIPHA format.
+///
+/// It returns the boundary matrix and the filtration values of the columns.
+pub fn read_dipha_boundary_matrix<R: Read>(
+    stream: &mut R,
+) -> Result<(BoundaryMatrix, Vec<f64>), DiphaError> {
+    read_header(stream, WEIGHTED_BOUNDARY_MATRIX)?;
+    if read_i64(stream)? != 0 {
+        return Err(DiphaError::Coboundary);
+    }
+    let count = to_usize(read_i64(stream)?, "number of columns")?;
+    let _max_dim = read_i64(stream)?;
+
+    // The vectors grow as the entries are read, since the sizes in the header may be
+    // larger than the file.
+    let mut dimensions = Vec::new();
+    for _ in 0..count {
+        dimensions.push(to_usize(read_i64(stream)?, "dimension")?);
+    }
+    let mut values = Vec::new();
+    for _ in 0..count {
+        values.push(read_f64(stream)?);
+    }
+    let mut offsets = Vec::new();
+    for _ in 0..count {
+        offsets.push(to_usize(read_i64(stream)?, "offset")?);
+    }
+    let entries_count = to_usize(read_i64(stream)?, "number of entries")?;
+    offsets.push(entries_count);
+    let mut entries = Vec::new();
+    for _ in 0..entries_count {
+        entries.push(to_usize(read_i64(stream)?, "row index")?);
+    }
+
+    let mut matrix = BoundaryMatrix::new();
+    for (column, dim) in dimensions.into_iter().enumerate() {
+        let (begin, end) = (offsets[column], offsets[column + 1]);
+        if begin > end || end > entries.len() {
+            return Err(DiphaError::InvalidValue("offset", begin as i64));
+        }
+        let rows = entries[begin..end].to_vec();
+        if let Some(row) = rows.iter().find(|row| **row >= column) {
+            return Err(DiphaError::NotTriangular {
+                column: column,
+                row: *row,
+            });
+        }
+        matrix.push(dim, rows);
+    }
+
+    Ok((matrix, values))
+}
+
+/// Writes a weighted boundary matrix in the DIPHA format.
+///
+/// The `i`-th element of `values` is the filtration value of the `i`-th column.
+pub fn write_dipha_boundary_matrix<W: Write>(
+    writer: &mut W,
+    matrix: &BoundaryMatrix,
+    values: &[f64],
+) -> std::io::Result<()> {
+    assert_eq!(matrix.len(), values.len());
+
+    write_i64(writer, DIPHA_MAGIC)?;
+    write_i64(writer, WEIGHTED_BOUNDARY_MATRIX)?;
+    write_i64(writer, 0)?;
+    write_i64(writer, matrix.len() as i64)?;
+    write_i64(
+        writer,
+        matrix.dimensions.iter().cloned().max().unwrap_or(0) as i64,
+    )?;
+    for dim in matrix.dimensions.iter() {
+        write_i64(writer, *dim as i64)?;
+    }
+    for value in values.iter() {
+        write_f64(writer, *value)?;
+    }
+    let mut offset = 0;
+    for column in matrix.columns.iter() {
+        write_i64(writer, offset as i64)?;
+        offset += column.len();
+    }
+    write_i64(writer, offset as i64)?;
+    for column in matrix.columns.iter() {
+        for i in column.iter() {
+            write_i64(writer, *i as i64)?;
+        }
+    }
+    Ok(())
+}
+
+/// Reads a persistence diagram in the DIPHA format.
+///
+/// It returns pairs of the dimension and the persistence pair. The essential classes,
+/// whose dimensions are stored as `-dim - 1`, have no death values.
+pub fn read_dipha_diagram<R: Read>(
+    stream: &mut R,
+) -> Result<Vec<(usize, Persistence<f64>)>, DiphaError> {
+    read_header(stream, PERSISTENCE_DIAGRAM)?;
+    let count = to_usize(read_i64(stream)?, "number of pairs")?;
+
+    let mut diagram = Vec::new();
+    for _ in 0..count {
+        let dim = read_i64(stream)?;
+        let birth = read_f64(stream)?;
+        let death = read_f64(stream)?;
+        if dim < 0 {
+            let dim = dim
+                .checked_neg()
+                .and_then(|d| d.checked_sub(1))
+                .ok_or(DiphaError::InvalidValue("dimension", dim))?;
+            diagram.push((dim as usize, Persistence(birth, None)));
+        } else {
+            diagram.push((dim as usize, Persistence(birth, Some(death))));
+        }
+    }
+
+    Ok(diagram)
+}
+
+/// Writes a persistence diagram in the DIPHA format.
+///
+/// The dimension of an essential class is written as `-dim - 1` and its death value as
+/// the positive infinity.
+pub fn write_dipha_diagram<W: Write>(
+    writer: &mut W,
+    diagram: &[(usize, Persistence<f64>)],
+) -> std::io::Result<()> {
+    write_i64(writer, DIPHA_MAGIC)?;
+    write_i64(writer, PERSISTENCE_DIAGRAM)?;
+    write_i64(writer, diagram.len() as i64)?;
+    for (dim, Persistence(birth, death)) in diagram.iter() {
+        match death {
+            Some(death) => {
+                write_i64(writer, *dim as i64)?;
+                write_f64(writer, *birth)?;
+                write_f64(writer, *death)?;
+            }
+            None => {
+                write_i64(writer, -(*dim as i64) - 1)?;
+                write_f64(writer, *birth)?;
+                write_f64(writer, f64::INFINITY)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn read_header<R: Read>(stream: &mut R, file_type: i64) -> Result<(), DiphaError> {
+    if read_i64(stream)? != DIPHA_MAGIC {
+        return Err(DiphaError::NotDipha);
+    }
+    let actual = read_i64(stream)?;
+    if actual != file_type {
+        return Err(DiphaError::UnexpectedFileType(actual));
+    }
+    Ok(())
+}
+
+fn to_usize(value: i64, what: &'static str) -> Result<usize, DiphaError> {
+    if value < 0 {
+        Err(DiphaError::InvalidValue(what, value))
+    } else {
+        Ok(value as usize)
+    }
+}
+
+fn read_f64<R: Read>(stream: &mut R) -> std::io::Result<f64> {
+    let mut bytes = [0; 8];
+    stream.read_exact(&mut bytes)?;
+    Ok(f64::from_bits(u64::from_le_bytes(bytes)))
+}
+
+fn write_f64<W: Write>(writer: &mut W, value: f64) -> std::io::Result<()> {
+    writer.write_all(&value.to_bits().to_le_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::boundary_matrix::BoundaryMatrix;
+    use crate::filtration::Filtration;
+    use crate::reader::dipha::{
+        read_dipha_boundary_matrix, read_dipha_diagram, write_dipha_boundary_matrix,
+        write_dipha_diagram, DiphaError,
+    };
+    use crate::simplex;
+
+    #[test]
+    fn test_dipha_formats() {
+        let mut filt = Filtration::new();
+        filt.push(simplex![0], 0.0).unwrap();
+        filt.push(simplex![1], 0.0).unwrap();
+        filt.push(simplex![2], 0.5).unwrap();
+        filt.push(simplex![0, 1], 1.0).unwrap();
+        filt.push(simplex![1, 2], 1.0).unwrap();
+        filt.push(simplex![0, 2], 1.5).unwrap();
+        filt.push(simplex![0, 1, 2], 2.0).unwrap();
+        let matrix = BoundaryMatrix::from_complex(&filt.complex).unwrap();
+
+        let mut bytes = Vec::new();
+        write_dipha_boundary_matrix(&mut bytes, &matrix, &filt.values).unwrap();
+        let (read, values) = read_dipha_boundary_matrix(&mut &bytes[..]).unwrap();
+        assert_eq!(read, matrix);
+        assert_eq!(values, filt.values);
+
+        let diagram = filt.diagram().unwrap();
+        let mut bytes = Vec::new();
+        write_dipha_diagram(&mut bytes, &diagram).unwrap();
+        assert_eq!(read_dipha_diagram(&mut &bytes[..]).unwrap(), diagram);
+
+        match read_dipha_boundary_matrix(&mut &bytes[..]) {
+            Err(DiphaError::UnexpectedFileType(2)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_dipha_truncated() {
+        let mut bytes = Vec::new();
+        for value in [8_067_171_840, 0, 0, i64::MAX, 2].iter() {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        match read_dipha_boundary_matrix(&mut &bytes[..]) {
+            Err(DiphaError::Io(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let mut bytes = Vec::new();
+        for value in [8_067_171_840, 2, i64::MAX].iter() {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        match read_dipha_diagram(&mut &bytes[..]) {
+            Err(DiphaError::Io(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let mut bytes = Vec::new();
+        for value in [8_067_171_840, 2, 1, i64::MIN, 0, 0].iter() {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        match read_dipha_diagram(&mut &bytes[..]) {
+            Err(DiphaError::InvalidValue("dimension", i64::MIN)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,246 @@
+use crate::distance::{DistanceMatrix, PointCloud};
+use failure::Fail;
+use std::io::BufRead;
+
+/// An error in reading a distance matrix or a point cloud
+///
+/// The line numbers start from one.
+#[derive(Debug, Fail)]
+pub enum DistanceReadError {
+    #[fail(display = "I/O error: {}", _0)]
+    Io(#[cause] std::io::Error),
+    #[fail(display = "line {}: cannot parse {:?}", line, token)]
+    Parse { line: usize, token: String },
+    #[fail(display = "the number of the entries {} is not triangular", _0)]
+    NotTriangular(usize),
+    #[fail(display = "line {}: expected {} entries but found {}", line, expected, found)]
+    WrongLength {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    #[fail(display = "line {}: a triplet must be `i j d`", line)]
+    InvalidTriplet { line: usize },
+    #[fail(display = "line {}: index {} is too large", line, index)]
+    TooLarge { line: usize, index: usize },
+}
+
+impl From<std::io::Error> for DistanceReadError {
+    fn from(e: std::io::Error) -> Self {
+        DistanceReadError::Io(e)
+    }
+}
+
+/// Reads a lower triangular distance matrix.
+///
+/// The input is the entries of the strictly lower triangular part ordered by rows,
+/// i.e. `d(1,0), d(2,0), d(2,1), d(3,0), ...`, separated by whitespaces or commas.
+/// Line breaks are not significant. This is the `lower-distance` format of Ripser.
+pub fn read_lower_distance_matrix<R: BufRead>(
+    stream: R,
+) -> Result<DistanceMatrix, DistanceReadError> {
+    let mut entries = Vec::new();
+    for row in read_rows(stream)?.into_iter() {
+        entries.extend(row.1);
+    }
+    let count = entries.len();
+    DistanceMatrix::from_lower_triangular(entries).ok_or(DistanceReadError::NotTriangular(count))
+}
+
+/// Reads a full distance matrix.
+///
+/// Each line is a row of the matrix whose entries are separated by whitespaces or commas.
+/// Only the strictly lower triangular part is used. This is the `distance` format of Ripser.
+pub fn read_full_distance_matrix<R: BufRead>(
+    stream: R,
+) -> Result<DistanceMatrix, DistanceReadError> {
+    let rows = read_rows(stream)?;
+    let len = rows.len();
+
+    let mut entries = Vec::with_capacity(len * len.saturating_sub(1) / 2);
+    for (i, (line, row)) in rows.into_iter().enumerate() {
+        if row.len() != len {
+            return Err(DistanceReadError::WrongLength {
+                line: line,
+                expected: len,
+                found: row.len(),
+            });
+        }
+        entries.extend_from_slice(&row[..i]);
+    }
+
+    let mut matrix = DistanceMatrix::new(len);
+    if len > 0 {
+        matrix = DistanceMatrix::from_lower_triangular(entries).unwrap();
+    }
+    Ok(matrix)
+}
+
+/// Reads a sparse distance matrix.
+///
+/// Each line is a triplet `i j d` that gives the distance `d` between the points `i` and `j`.
+/// The number of the points is the largest index plus one and the distances not given are
+/// infinite. This is the `sparse` format of Ripser.
+///
+/// Each triplet adds at most two points, so the number of the points must be at most twice
+/// the number of the triplets. A larger index is rejected rather than allocating a dense
+/// matrix for the points without distances.
+pub fn read_sparse_distance_matrix<R: BufRead>(
+    stream: R,
+) -> Result<DistanceMatrix, DistanceReadError> {
+    let mut triplets = Vec::new();
+    let mut len = 0;
+    let mut largest = None;
+
+    for (index, line) in stream.lines().enumerate() {
+        let line = line?;
+        let tokens: Vec<&str> = split_tokens(&line).collect();
+        if tokens.is_empty() {
+            continue;
+        }
+        if tokens.len() != 3 {
+            return Err(DistanceReadError::InvalidTriplet { line: index + 1 });
+        }
+        let parse_index = |token: &str| {
+            token.parse::<usize>().map_err(|_| DistanceReadError::Parse {
+                line: index + 1,
+                token: token.to_string(),
+            })
+        };
+        let i = parse_index(tokens[0])?;
+        let j = parse_index(tokens[1])?;
+        let d = parse_value(index + 1, tokens[2])?;
+        let k = i.max(j);
+        let end = k.checked_add(1).ok_or(DistanceReadError::TooLarge {
+            line: index + 1,
+            index: k,
+        })?;
+        if end > len {
+            len = end;
+            largest = Some((index + 1, k));
+        }
+        triplets.push((i, j, d));
+    }
+    if let Some((line, k)) = largest {
+        if len > triplets.len().saturating_mul(2) {
+            return Err(DistanceReadError::TooLarge {
+                line: line,
+                index: k,
+            });
+        }
+    }
+
+    let mut matrix = DistanceMatrix::new(len);
+    for (i, j, d) in triplets.into_iter() {
+        if i != j {
+            matrix.set_distance(i, j, d);
+        }
+    }
+    Ok(matrix)
+}
+
+/// Reads a point cloud in the Euclidean space.
+///
+/// Each line is a point whose coordinates are separated by whitespaces or commas.
+/// This is the `point-cloud` format of Ripser.
+pub fn read_point_cloud<R: BufRead>(stream: R) -> Result<PointCloud, DistanceReadError> {
+    let rows = read_rows(stream)?;
+
+    if let Some((_, first)) = rows.first() {
+        let dim = first.len();
+        if let Some((line, row)) = rows.iter().find(|(_, row)| row.len() != dim) {
+            return Err(DistanceReadError::WrongLength {
+                line: *line,
+                expected: dim,
+                found: row.len(),
+            });
+        }
+    }
+
+    Ok(PointCloud::new(rows.into_iter().map(|(_, row)| row).collect()))
+}
+
+/// Reads the non-blank lines of numbers with their line numbers.
+fn read_rows<R: BufRead>(stream: R) -> Result<Vec<(usize, Vec<f64>)>, DistanceReadError> {
+    let mut rows = Vec::new();
+
+    for (index, line) in stream.lines().enumerate() {
+        let line = line?;
+        let row = split_tokens(&line)
+            .map(|token| parse_value(index + 1, token))
+            .collect::<Result<Vec<f64>, _>>()?;
+        if !row.is_empty() {
+            rows.push((index + 1, row));
+        }
+    }
+
+    Ok(rows)
+}
+
+fn split_tokens(line: &str) -> impl Iterator<Item = &str> {
+    line.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|token| !token.is_empty())
+}
+
+fn parse_value(line: usize, token: &str) -> Result<f64, DistanceReadError> {
+    token.parse::<f64>().map_err(|_| DistanceReadError::Parse {
+        line: line,
+        token: token.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::distance::Distance;
+    use crate::reader::distance::{
+        read_full_distance_matrix, read_lower_distance_matrix, read_point_cloud,
+        read_sparse_distance_matrix, DistanceReadError,
+    };
+
+    #[test]
+    fn test_read_distance_matrices() {
+        let lower = read_lower_distance_matrix(&b"1\n2, 3\n4 5 6\n"[..]).unwrap();
+        assert_eq!(lower.len(), 4);
+        assert_eq!(lower.distance(2, 1), 3.0);
+        assert_eq!(lower.distance(1, 3), 5.0);
+
+        let full =
+            read_full_distance_matrix(&b"0 1 2 4\n1 0 3 5\n2 3 0 6\n4 5 6 0\n"[..]).unwrap();
+        assert_eq!(full, lower);
+
+        let sparse = read_sparse_distance_matrix(&b"0 1 1.5\n\n3 1 2\n"[..]).unwrap();
+        assert_eq!(sparse.len(), 4);
+        assert_eq!(sparse.distance(1, 0), 1.5);
+        assert_eq!(sparse.distance(1, 3), 2.0);
+        assert_eq!(sparse.distance(0, 2), f64::INFINITY);
+        match read_sparse_distance_matrix(&b"1000000000 0 1\n"[..]) {
+            Err(DistanceReadError::TooLarge {
+                line: 1,
+                index: 1000000000,
+            }) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        let huge = format!("0 1 1\n{} 1 1\n", usize::MAX);
+        match read_sparse_distance_matrix(huge.as_bytes()) {
+            Err(DistanceReadError::TooLarge { line: 2, .. }) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        match read_lower_distance_matrix(&b"1 2\n"[..]) {
+            Err(DistanceReadError::NotTriangular(2)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match read_full_distance_matrix(&b"0 1\n1\n"[..]) {
+            Err(DistanceReadError::WrongLength { line: 2, .. }) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_read_point_cloud() {
+        let cloud = read_point_cloud(&b"0,0\n3 4\n"[..]).unwrap();
+        assert_eq!(cloud.distance(0, 1), 5.0);
+        assert!(read_point_cloud(&b"0 0\n1\n"[..]).is_err());
+        assert!(read_point_cloud(&b"0 x\n"[..]).is_err());
+    }
+}
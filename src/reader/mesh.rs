@@ -0,0 +1,718 @@
+use crate::complex::Complex;
+use crate::filtration::Filtration;
+use crate::indexed_vec::IndexedVec;
+use crate::simplex::Simplex;
+use failure::Fail;
+use std::collections::BTreeSet;
+use std::io::{BufRead, Read};
+
+/// An error in reading a mesh
+///
+/// The line numbers start from one and the faces are numbered from zero.
+#[derive(Debug, Fail)]
+pub enum MeshError {
+    #[fail(display = "I/O error: {}", _0)]
+    Io(#[cause] std::io::Error),
+    #[fail(display = "line {}: cannot parse {:?}", line, token)]
+    Parse { line: usize, token: String },
+    #[fail(display = "line {}: invalid header", line)]
+    InvalidHeader { line: usize },
+    #[fail(display = "line {}: too few numbers", line)]
+    TooFewNumbers { line: usize },
+    #[fail(display = "unexpected end of file")]
+    UnexpectedEnd,
+    #[fail(display = "face {}: vertex index {} is out of range", face, index)]
+    VertexOutOfRange { face: usize, index: i64 },
+    #[fail(display = "invalid list length {}", _0)]
+    InvalidListLength(f64),
+    #[fail(display = "vertex {}: the value is not a number", _0)]
+    NotANumber(usize),
+}
+
+impl From<std::io::Error> for MeshError {
+    fn from(e: std::io::Error) -> Self {
+        MeshError::Io(e)
+    }
+}
+
+/// The format of a mesh file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeshFormat {
+    /// Object File Format
+    Off,
+    /// Wavefront OBJ
+    Obj,
+    /// Polygon File Format, ASCII or binary
+    Ply,
+}
+
+impl std::str::FromStr for MeshFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "off" => Ok(MeshFormat::Off),
+            "obj" => Ok(MeshFormat::Obj),
+            "ply" => Ok(MeshFormat::Ply),
+            _ => Err(format!("unknown mesh format: {}", s)),
+        }
+    }
+}
+
+/// Polygonal mesh
+///
+/// The faces are given by the indices of their vertices, which are numbered from zero.
+/// A face may have any number of vertices, e.g. a line segment of a mesh has two.
+/// The attributes are the scalar values of the vertices with their names, such as the
+/// coordinates `x`, `y` and `z`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Mesh {
+    pub vertex_count: usize,
+    pub faces: Vec<Vec<usize>>,
+    pub attributes: Vec<(String, Vec<f64>)>,
+}
+
+impl Mesh {
+    /// Returns the values of the attribute of the vertices.
+    pub fn attribute(&self, name: &str) -> Option<&[f64]> {
+        self.attributes
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, values)| &values[..])
+    }
+
+    /// Builds the simplicial complex of the mesh.
+    ///
+    /// The complex consists of the vertices, the faces and all their faces. A polygon with
+    /// more than three vertices is divided into the triangles sharing its first vertex.
+    /// The simplices are ordered by dimension.
+    pub fn complex(&self) -> Complex<IndexedVec<Simplex>, Simplex> {
+        let mut simplices = BTreeSet::new();
+        for v in 0..self.vertex_count {
+            simplices.insert((0, vec![v]));
+        }
+        for face in self.faces.iter() {
+            let mut vertices = Vec::new();
+            for v in face.iter() {
+                if !vertices.contains(v) {
+                    vertices.push(*v);
+                }
+            }
+            if vertices.len() <= 3 {
+                insert_closure(&mut simplices, vertices);
+            } else {
+                for i in 1..vertices.len() - 1 {
+                    insert_closure(
+                        &mut simplices,
+                        vec![vertices[0], vertices[i], vertices[i + 1]],
+                    );
+                }
+            }
+        }
+
+        let mut complex = Complex::new();
+        for (_, v) in simplices.into_iter() {
+            complex.push_unchecked(Simplex::new(v));
+        }
+        complex
+    }
+
+    /// Builds the lower-star filtration of the complex of the mesh.
+    ///
+    /// The `i`-th element of `values` is the value of the vertex `i`.
+    /// It returns an error if a value is NaN, which the PLY and OBJ files may contain.
+    pub fn lower_star_filtration(
+        &self,
+        values: &[f64],
+    ) -> Result<Filtration<Simplex, f64>, MeshError> {
+        assert_eq!(values.len(), self.vertex_count);
+        if let Some(vertex) = values.iter().position(|value| value.is_nan()) {
+            return Err(MeshError::NotANumber(vertex));
+        }
+        Ok(Filtration::lower_star(&self.complex(), values))
+    }
+}
+
+fn insert_closure(simplices: &mut BTreeSet<(usize, Vec<usize>)>, mut vertices: Vec<usize>) {
+    vertices.sort();
+    let n = vertices.len();
+    for mask in 1..(1usize << n) {
+        let face: Vec<usize> = (0..n)
+            .filter(|i| mask & (1 << i) != 0)
+            .map(|i| vertices[i])
+            .collect();
+        simplices.insert((face.len() - 1, face));
+    }
+}
+
+/// Reads a mesh in the format.
+pub fn read_mesh<R: BufRead>(stream: R, format: MeshFormat) -> Result<Mesh, MeshError> {
+    match format {
+        MeshFormat::Off => read_off(stream),
+        MeshFormat::Obj => read_obj(stream),
+        MeshFormat::Ply => read_ply(stream),
+    }
+}
+
+/// Reads a mesh in the Object File Format (OFF).
+///
+/// The header `OFF` is followed by the numbers of the vertices, the faces and the edges,
+/// the coordinates of the vertices and the faces written as `k v1 ... vk`. The variants
+/// `COFF`, `NOFF` and `CNOFF` of the header are accepted and the colors and the normals are
+/// ignored. The texts after `#` are comments. The attributes are the coordinates
+/// `x`, `y` and `z`.
+pub fn read_off<R: BufRead>(stream: R) -> Result<Mesh, MeshError> {
+    let mut lines = Vec::new();
+    for (index, line) in stream.lines().enumerate() {
+        let line = line?;
+        let line = line.split('#').next().unwrap_or("");
+        let tokens: Vec<String> = line.split_whitespace().map(|t| t.to_string()).collect();
+        if !tokens.is_empty() {
+            lines.push((index + 1, tokens));
+        }
+    }
+    let mut lines = lines.into_iter();
+
+    let (line, mut tokens) = lines.next().ok_or(MeshError::UnexpectedEnd)?;
+    if !["OFF", "COFF", "NOFF", "CNOFF"].contains(&tokens[0].as_str()) {
+        return Err(MeshError::InvalidHeader { line: line });
+    }
+    tokens.remove(0);
+    let (line, tokens) = if tokens.is_empty() {
+        lines.next().ok_or(MeshError::UnexpectedEnd)?
+    } else {
+        (line, tokens)
+    };
+    let counts = parse_numbers::<usize>(line, &tokens, 2)?;
+    let (vertex_count, face_count) = (counts[0], counts[1]);
+
+    let mut coords = vec![Vec::new(); 3];
+    for _ in 0..vertex_count {
+        let (line, tokens) = lines.next().ok_or(MeshError::UnexpectedEnd)?;
+        for (axis, value) in parse_numbers::<f64>(line, &tokens, 3)?
+            .into_iter()
+            .enumerate()
+        {
+            coords[axis].push(value);
+        }
+    }
+
+    let mut faces = Vec::new();
+    for _ in 0..face_count {
+        let (line, tokens) = lines.next().ok_or(MeshError::UnexpectedEnd)?;
+        let k = parse_numbers::<usize>(line, &tokens, 1)?[0];
+        let face = parse_numbers::<usize>(line, &tokens[1..], k)?;
+        check_face(faces.len(), &face, vertex_count)?;
+        faces.push(face);
+    }
+
+    Ok(Mesh {
+        vertex_count: vertex_count,
+        faces: faces,
+        attributes: coordinate_attributes(coords),
+    })
+}
+
+/// Reads a mesh in the Wavefront OBJ format.
+///
+/// The vertices are the lines `v x y z` and the faces are the lines `f v1 v2 ... vk`,
+/// where a vertex may be written as `v/vt/vn`. The vertices are numbered from one and
+/// the negative numbers refer to the vertices relative to the end. The line segments of
+/// the lines `l v1 v2 ... vk` are the faces with two vertices, and the other lines are
+/// ignored. The attributes are the coordinates `x`, `y` and `z`.
+pub fn read_obj<R: BufRead>(stream: R) -> Result<Mesh, MeshError> {
+    let mut coords = vec![Vec::new(); 3];
+    let mut faces = Vec::new();
+    // The faces with the raw indices, which are checked after reading all the vertices.
+    let mut indices = Vec::new();
+
+    for (index, line) in stream.lines().enumerate() {
+        let line = line?;
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        match tokens.first() {
+            Some(&"v") => {
+                let values = parse_numbers::<f64>(index + 1, &tokens[1..], 3)?;
+                for (axis, value) in values.into_iter().enumerate() {
+                    coords[axis].push(value);
+                }
+            }
+            Some(&"f") | Some(&"l") => {
+                let mut face = Vec::with_capacity(tokens.len() - 1);
+                for token in tokens[1..].iter() {
+                    let vertex = token.split('/').next().unwrap_or("");
+                    let i = vertex.parse::<i64>().map_err(|_| MeshError::Parse {
+                        line: index + 1,
+                        token: token.to_string(),
+                    })?;
+                    face.push(if i < 0 {
+                        coords[0].len() as i64 + i
+                    } else {
+                        i - 1
+                    });
+                }
+                if tokens[0] == "f" {
+                    indices.push(face);
+                } else {
+                    indices.extend(face.windows(2).map(|edge| edge.to_vec()));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let vertex_count = coords[0].len();
+    for (n, face) in indices.into_iter().enumerate() {
+        let mut vertices = Vec::with_capacity(face.len());
+        for i in face.into_iter() {
+            if i < 0 || i as usize >= vertex_count {
+                return Err(MeshError::VertexOutOfRange { face: n, index: i });
+            }
+            vertices.push(i as usize);
+        }
+        faces.push(vertices);
+    }
+
+    Ok(Mesh {
+        vertex_count: vertex_count,
+        faces: faces,
+        attributes: coordinate_attributes(coords),
+    })
+}
+
+/// The scalar types of the PLY format
+#[derive(Debug, Clone, Copy)]
+enum PlyType {
+    Int8,
+    UInt8,
+    Int16,
+    UInt16,
+    Int32,
+    UInt32,
+    Float32,
+    Float64,
+}
+
+impl PlyType {
+    fn parse(s: &str) -> Option<PlyType> {
+        match s {
+            "char" | "int8" => Some(PlyType::Int8),
+            "uchar" | "uint8" => Some(PlyType::UInt8),
+            "short" | "int16" => Some(PlyType::Int16),
+            "ushort" | "uint16" => Some(PlyType::UInt16),
+            "int" | "int32" => Some(PlyType::Int32),
+            "uint" | "uint32" => Some(PlyType::UInt32),
+            "float" | "float32" => Some(PlyType::Float32),
+            "double" | "float64" => Some(PlyType::Float64),
+            _ => None,
+        }
+    }
+
+    fn size(self) -> usize {
+        match self {
+            PlyType::Int8 | PlyType::UInt8 => 1,
+            PlyType::Int16 | PlyType::UInt16 => 2,
+            PlyType::Int32 | PlyType::UInt32 | PlyType::Float32 => 4,
+            PlyType::Float64 => 8,
+        }
+    }
+
+    fn read<R: Read>(self, stream: &mut R, big_endian: bool) -> std::io::Result<f64> {
+        let size = self.size();
+        let mut bytes = [0; 8];
+        stream.read_exact(&mut bytes[..size])?;
+        if big_endian {
+            bytes[..size].reverse();
+        }
+        let b = bytes;
+        Ok(match self {
+            PlyType::Int8 => f64::from(b[0] as i8),
+            PlyType::UInt8 => f64::from(b[0]),
+            PlyType::Int16 => f64::from(i16::from_le_bytes([b[0], b[1]])),
+            PlyType::UInt16 => f64::from(u16::from_le_bytes([b[0], b[1]])),
+            PlyType::Int32 => f64::from(i32::from_le_bytes([b[0], b[1], b[2], b[3]])),
+            PlyType::UInt32 => f64::from(u32::from_le_bytes([b[0], b[1], b[2], b[3]])),
+            PlyType::Float32 => {
+                f64::from(f32::from_bits(u32::from_le_bytes([b[0], b[1], b[2], b[3]])))
+            }
+            PlyType::Float64 => f64::from_bits(u64::from_le_bytes(b)),
+        })
+    }
+}
+
+/// A property of an element of the PLY format
+///
+/// A scalar property has its name and type, and a list property has the types of
+/// the length and the items.
+#[derive(Debug)]
+enum PlyProperty {
+    Scalar(String, PlyType),
+    List(PlyType, PlyType),
+}
+
+#[derive(Debug)]
+struct PlyElement {
+    name: String,
+    count: usize,
+    properties: Vec<PlyProperty>,
+}
+
+/// Reads a mesh in the ASCII or binary Polygon File Format (PLY).
+///
+/// The faces are the first list property of the element `face`, and the attributes are
+/// the scalar properties of the element `vertex`. The other elements are ignored.
+pub fn read_ply<R: BufRead>(mut stream: R) -> Result<Mesh, MeshError> {
+    let mut line_number = 0;
+    let mut format = String::new();
+    let mut elements: Vec<PlyElement> = Vec::new();
+
+    loop {
+        let mut line = String::new();
+        if stream.read_line(&mut line)? == 0 {
+            return Err(MeshError::UnexpectedEnd);
+        }
+        line_number += 1;
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let invalid = || MeshError::InvalidHeader { line: line_number };
+        if line_number == 1 {
+            if tokens != ["ply"] {
+                return Err(invalid());
+            }
+            continue;
+        }
+        if tokens.is_empty() || tokens[0] == "comment" || tokens[0] == "obj_info" {
+            continue;
+        }
+        match tokens[..] {
+            ["format", f, _] => match f {
+                "ascii" | "binary_little_endian" | "binary_big_endian" => format = f.to_string(),
+                _ => return Err(invalid()),
+            },
+            ["element", name, count] => elements.push(PlyElement {
+                name: name.to_string(),
+                count: count.parse().map_err(|_| invalid())?,
+                properties: Vec::new(),
+            }),
+            ["property", "list", len, item, _] => {
+                let len = PlyType::parse(len).ok_or_else(invalid)?;
+                let item = PlyType::parse(item).ok_or_else(invalid)?;
+                let element = elements.last_mut().ok_or_else(invalid)?;
+                element.properties.push(PlyProperty::List(len, item));
+            }
+            ["property", ty, name] => {
+                let ty = PlyType::parse(ty).ok_or_else(invalid)?;
+                let element = elements.last_mut().ok_or_else(invalid)?;
+                element
+                    .properties
+                    .push(PlyProperty::Scalar(name.to_string(), ty));
+            }
+            ["end_header"] => break,
+            _ => return Err(invalid()),
+        }
+    }
+
+    let mut mesh = Mesh::default();
+    let mut faces = Vec::new();
+    let mut body = match format.as_str() {
+        "ascii" => PlyBody::Ascii {
+            lines: stream.lines(),
+            line: line_number,
+        },
+        "binary_little_endian" => PlyBody::Binary {
+            stream: stream,
+            big_endian: false,
+        },
+        "binary_big_endian" => PlyBody::Binary {
+            stream: stream,
+            big_endian: true,
+        },
+        _ => return Err(MeshError::InvalidHeader { line: line_number }),
+    };
+
+    for element in elements.iter() {
+        if element.name == "vertex" {
+            mesh.vertex_count = element.count;
+            for property in element.properties.iter() {
+                if let PlyProperty::Scalar(name, _) = property {
+                    mesh.attributes.push((name.clone(), Vec::new()));
+                }
+            }
+        }
+        for _ in 0..element.count {
+            let values = body.read_element(element)?;
+            if element.name == "vertex" {
+                let scalars =
+                    element
+                        .properties
+                        .iter()
+                        .zip(values.iter())
+                        .filter_map(|(property, value)| match property {
+                            PlyProperty::Scalar(..) => Some(value[0]),
+                            PlyProperty::List(..) => None,
+                        });
+                for ((_, attribute), value) in mesh.attributes.iter_mut().zip(scalars) {
+                    attribute.push(value);
+                }
+            } else if element.name == "face" {
+                if let Some(index) = element.properties.iter().position(|p| match p {
+                    PlyProperty::List(..) => true,
+                    PlyProperty::Scalar(..) => false,
+                }) {
+                    faces.push(values[index].clone());
+                }
+            }
+        }
+    }
+
+    for (n, face) in faces.into_iter().enumerate() {
+        let mut vertices = Vec::with_capacity(face.len());
+        for i in face.into_iter() {
+            if i < 0.0 || i.fract() != 0.0 || i as usize >= mesh.vertex_count {
+                return Err(MeshError::VertexOutOfRange {
+                    face: n,
+                    index: i as i64,
+                });
+            }
+            vertices.push(i as usize);
+        }
+        mesh.faces.push(vertices);
+    }
+
+    Ok(mesh)
+}
+
+/// The body of a PLY file after the header
+enum PlyBody<R: BufRead> {
+    Ascii {
+        lines: std::io::Lines<R>,
+        line: usize,
+    },
+    Binary {
+        stream: R,
+        big_endian: bool,
+    },
+}
+
+impl<R: BufRead> PlyBody<R> {
+    /// Reads the values of the properties of an element.
+    ///
+    /// A scalar property has a single value and a list property has its items.
+    fn read_element(&mut self, element: &PlyElement) -> Result<Vec<Vec<f64>>, MeshError> {
+        let mut values = Vec::with_capacity(element.properties.len());
+        match self {
+            PlyBody::Ascii { lines, line } => {
+                let text = loop {
+                    let text = lines.next().ok_or(MeshError::UnexpectedEnd)??;
+                    *line += 1;
+                    if !text.trim().is_empty() {
+                        break text;
+                    }
+                };
+                let mut tokens = text.split_whitespace();
+                let mut next = || -> Result<f64, MeshError> {
+                    let token = tokens
+                        .next()
+                        .ok_or(MeshError::TooFewNumbers { line: *line })?;
+                    token.parse::<f64>().map_err(|_| MeshError::Parse {
+                        line: *line,
+                        token: token.to_string(),
+                    })
+                };
+                for property in element.properties.iter() {
+                    match property {
+                        PlyProperty::Scalar(..) => values.push(vec![next()?]),
+                        PlyProperty::List(..) => {
+                            let len = list_length(next()?)?;
+                            let mut items = Vec::new();
+                            for _ in 0..len {
+                                items.push(next()?);
+                            }
+                            values.push(items);
+                        }
+                    }
+                }
+            }
+            PlyBody::Binary { stream, big_endian } => {
+                for property in element.properties.iter() {
+                    match property {
+                        PlyProperty::Scalar(_, ty) => {
+                            values.push(vec![ty.read(stream, *big_endian)?])
+                        }
+                        PlyProperty::List(len, item) => {
+                            let len = list_length(len.read(stream, *big_endian)?)?;
+                            let mut items = Vec::new();
+                            for _ in 0..len {
+                                items.push(item.read(stream, *big_endian)?);
+                            }
+                            values.push(items);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(values)
+    }
+}
+
+/// Converts the length of a list property, which must be a non-negative integer.
+///
+/// The items are read one by one, so a length larger than the file is reported as
+/// the end of the file.
+fn list_length(len: f64) -> Result<usize, MeshError> {
+    if len >= 0.0 && len.fract() == 0.0 && len <= u32::MAX as f64 {
+        Ok(len as usize)
+    } else {
+        Err(MeshError::InvalidListLength(len))
+    }
+}
+
+/// Parses the first `count` tokens.
+fn parse_numbers<T: std::str::FromStr>(
+    line: usize,
+    tokens: &[impl AsRef<str>],
+    count: usize,
+) -> Result<Vec<T>, MeshError> {
+    if tokens.len() < count {
+        return Err(MeshError::TooFewNumbers { line: line });
+    }
+    tokens[..count]
+        .iter()
+        .map(|token| {
+            token.as_ref().parse::<T>().map_err(|_| MeshError::Parse {
+                line: line,
+                token: token.as_ref().to_string(),
+            })
+        })
+        .collect()
+}
+
+fn check_face(n: usize, face: &[usize], vertex_count: usize) -> Result<(), MeshError> {
+    match face.iter().find(|v| **v >= vertex_count) {
+        Some(v) => Err(MeshError::VertexOutOfRange {
+            face: n,
+            index: *v as i64,
+        }),
+        None => Ok(()),
+    }
+}
+
+fn coordinate_attributes(coords: Vec<Vec<f64>>) -> Vec<(String, Vec<f64>)> {
+    ["x", "y", "z"]
+        .iter()
+        .map(|name| name.to_string())
+        .zip(coords)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::reader::mesh::{read_mesh, read_obj, read_off, read_ply, MeshError, MeshFormat};
+    use crate::traits::*;
+    use crate::Persistence;
+
+    #[test]
+    fn test_read_off() {
+        // The boundary of a tetrahedron
+        let text = b"OFF\n# a tetrahedron\n4 4 6\n0 0 0\n1 0 0\n0 1 0\n0 0 1\n\
+                     3 0 1 2\n3 0 1 3\n3 0 2 3\n3 1 2 3\n";
+        let mesh = read_off(&text[..]).unwrap();
+        assert_eq!(mesh.vertex_count, 4);
+        assert_eq!(mesh.complex().basis.len(), 4 + 6 + 4);
+
+        let filt = mesh
+            .lower_star_filtration(mesh.attribute("z").unwrap())
+            .unwrap();
+        let diagram = filt.diagram().unwrap();
+        let essential = diagram
+            .iter()
+            .filter(|(_, pers)| pers.is_essential())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            essential,
+            vec![&(0, Persistence(0.0, None)), &(2, Persistence(1.0, None))]
+        );
+
+        match read_off(&b"OFF 1 1 0\n0 0 0\n3 0 1 2\n"[..]) {
+            Err(MeshError::VertexOutOfRange { face: 0, index: 1 }) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(read_off(&b"COFF 1 0 0\n0 0 0 255 0 0\n"[..]).is_ok());
+        match read_off(&b"NOTOFF 1 0 0\n0 0 0\n"[..]) {
+            Err(MeshError::InvalidHeader { line: 1 }) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_read_obj() {
+        let text = b"# a square\nv 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nvn 0 0 1\n\
+                     f 1//1 2//1 -2//1 -1//1\nl 1 3\n";
+        let mesh = read_mesh(&text[..], MeshFormat::Obj).unwrap();
+        assert_eq!(mesh.faces, vec![vec![0, 1, 2, 3], vec![0, 2]]);
+        // The square is divided into two triangles along the diagonal.
+        assert_eq!(mesh.complex().basis.len(), 4 + 5 + 2);
+
+        assert!(read_obj(&b"v 0 0 0\nf 1 2 3\n"[..]).is_err());
+    }
+
+    #[test]
+    fn test_read_ply() {
+        let header = "ply\nformat {}\ncomment a triangle\nelement vertex 3\n\
+                      property float x\nproperty float y\nproperty float z\n\
+                      property double quality\nelement face 1\n\
+                      property list uchar int vertex_indices\nend_header\n";
+
+        let ascii = header.replace("{}", "ascii 1.0") + "0 0 0 2\n1 0 0 0\n0 1 0 1\n3 0 1 2\n";
+        let mesh = read_ply(ascii.as_bytes()).unwrap();
+        assert_eq!(mesh.faces, vec![vec![0, 1, 2]]);
+        assert_eq!(mesh.attribute("quality").unwrap(), &[2.0, 0.0, 1.0][..]);
+
+        let filt = mesh
+            .lower_star_filtration(mesh.attribute("quality").unwrap())
+            .unwrap();
+        assert_eq!(filt.values, vec![0.0, 1.0, 1.0, 2.0, 2.0, 2.0, 2.0]);
+
+        let nan = header.replace("{}", "ascii 1.0") + "0 0 0 2\n1 0 0 nan\n0 1 0 1\n3 0 1 2\n";
+        let nan = read_ply(nan.as_bytes()).unwrap();
+        match nan.lower_star_filtration(nan.attribute("quality").unwrap()) {
+            Err(MeshError::NotANumber(1)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        for len in &["-1", "2.5", "1e30"] {
+            let text = header.replace("{}", "ascii 1.0")
+                + &format!("0 0 0 2\n1 0 0 0\n0 1 0 1\n{} 0 1 2\n", len);
+            match read_ply(text.as_bytes()) {
+                Err(MeshError::InvalidListLength(_)) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+
+        let mut binary = header.replace("{}", "binary_big_endian 1.0").into_bytes();
+        for (position, quality) in [[0.0f32, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]]
+            .iter()
+            .zip([2.0f64, 0.0, 1.0].iter())
+        {
+            for x in position.iter() {
+                binary.extend_from_slice(&x.to_bits().to_be_bytes());
+            }
+            binary.extend_from_slice(&quality.to_bits().to_be_bytes());
+        }
+        binary.push(3);
+        for i in 0..3i32 {
+            binary.extend_from_slice(&i.to_be_bytes());
+        }
+        assert_eq!(read_ply(&binary[..]).unwrap(), mesh);
+        assert!(read_ply(&binary[..binary.len() - 1]).is_err());
+
+        // A huge list length is reported as the end of the file.
+        let mut huge = header
+            .replace("{}", "binary_big_endian 1.0")
+            .replace("list uchar int", "list uint int")
+            .into_bytes();
+        huge.extend_from_slice(&binary[binary.len() - 13 - 3 * 20..binary.len() - 13]);
+        huge.extend_from_slice(&u32::MAX.to_be_bytes());
+        match read_ply(&huge[..]) {
+            Err(MeshError::Io(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
@@ -1 +1,7 @@
+pub mod dipha;
+pub mod distance;
+pub mod mesh;
+pub mod perseus;
+pub mod phat;
 pub mod simpcomp;
+pub mod volume;
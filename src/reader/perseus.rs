@@ -0,0 +1,329 @@
+use crate::cube::{voxel_filtration, CubeError, CubicalFiltration};
+use crate::filtration::Filtration;
+use crate::simplex::Simplex;
+use failure::Fail;
+use std::collections::BTreeMap;
+use std::io::BufRead;
+
+/// An error in reading a file in the Perseus formats
+///
+/// The line numbers start from one.
+#[derive(Debug, Fail)]
+pub enum PerseusError {
+    #[fail(display = "I/O error: {}", _0)]
+    Io(#[cause] std::io::Error),
+    #[fail(display = "line {}: cannot parse {:?}", line, token)]
+    Parse { line: usize, token: String },
+    #[fail(
+        display = "line {}: expected {} numbers, found {}",
+        line, expected, found
+    )]
+    WrongLength {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    #[fail(display = "line {}: {} {} is too large", line, what, value)]
+    TooLarge {
+        line: usize,
+        what: &'static str,
+        value: usize,
+    },
+    #[fail(display = "unexpected end of file")]
+    UnexpectedEnd,
+    #[fail(display = "{}", _0)]
+    Cube(#[cause] CubeError),
+}
+
+impl From<std::io::Error> for PerseusError {
+    fn from(e: std::io::Error) -> Self {
+        PerseusError::Io(e)
+    }
+}
+
+impl From<CubeError> for PerseusError {
+    fn from(e: CubeError) -> Self {
+        PerseusError::Cube(e)
+    }
+}
+
+/// The largest dimension of the simplices and the cubical grids
+///
+/// A top simplex of dimension `d` has `2^(d+1) - 1` faces, so larger ones are not practical.
+pub const MAX_DIMENSION: usize = 32;
+
+/// Reads a non-uniform simplicial complex in the Perseus format (`nmfsimtop`).
+///
+/// The first line is the number of the coordinates of a vertex, which is `1`.
+/// Each of the other lines is a top simplex written as `d v0 v1 ... vd b`, where `d` is
+/// the dimension and `b` is the birth time. The faces of the top simplices are added and
+/// each of them is born with the earliest simplex containing it.
+pub fn read_perseus_simplicial<R: BufRead>(
+    stream: R,
+) -> Result<Filtration<Simplex, usize>, PerseusError> {
+    let mut lines = read_lines(stream)?.into_iter();
+    match lines.next() {
+        Some((line, numbers)) => check_length(line, &numbers, 1)?,
+        None => return Err(PerseusError::UnexpectedEnd),
+    }
+
+    let mut births = BTreeMap::new();
+    for (line, numbers) in lines {
+        let dim = check_dimension(line, numbers[0])?;
+        check_length(line, &numbers, dim + 3)?;
+        let mut vertices = numbers[1..dim + 2].to_vec();
+        vertices.sort();
+        vertices.dedup();
+        let birth = numbers[dim + 2];
+
+        let n = vertices.len();
+        let count = 1usize
+            .checked_shl(n as u32)
+            .ok_or(PerseusError::TooLarge {
+                line: line,
+                what: "dimension",
+                value: dim,
+            })?;
+        for mask in 1..count {
+            let face: Vec<usize> = (0..n)
+                .filter(|i| mask & (1 << i) != 0)
+                .map(|i| vertices[i])
+                .collect();
+            insert_birth(&mut births, face, birth);
+        }
+    }
+
+    Ok(Filtration::from_unsorted(
+        births
+            .into_iter()
+            .map(|(vertices, birth)| (Simplex::new(vertices), birth))
+            .collect(),
+    ))
+}
+
+/// Reads a dense cubical grid in the Perseus format (`cubtop`).
+///
+/// The file is a sequence of integers separated by whitespaces: the dimension `d`,
+/// the sizes of the `d` axes and the birth times of the voxels, where the first coordinate
+/// changes fastest. The voxels with negative birth times are absent. The faces of the
+/// voxels are added and each of them is born with the earliest voxel containing it.
+pub fn read_perseus_cubical_dense<R: BufRead>(
+    stream: R,
+) -> Result<CubicalFiltration<usize>, PerseusError> {
+    let mut tokens = Vec::new();
+    for (index, line) in stream.lines().enumerate() {
+        let line = line?;
+        for token in line.split_whitespace() {
+            let number = token.parse::<i64>().map_err(|_| PerseusError::Parse {
+                line: index + 1,
+                token: token.to_string(),
+            })?;
+            tokens.push((index + 1, token.to_string(), number));
+        }
+    }
+    let mut tokens = tokens.into_iter();
+    let mut next_count = || match tokens.next() {
+        Some((line, _, number)) if number >= 0 => Ok((line, number as usize)),
+        Some((line, token, _)) => Err(PerseusError::Parse {
+            line: line,
+            token: token,
+        }),
+        None => Err(PerseusError::UnexpectedEnd),
+    };
+
+    let (line, dim) = next_count()?;
+    let dim = check_dimension(line, dim)?;
+    let mut sizes = Vec::with_capacity(dim);
+    let mut count = 1usize;
+    for _ in 0..dim {
+        let (line, size) = next_count()?;
+        count = count.checked_mul(size).ok_or(PerseusError::TooLarge {
+            line: line,
+            what: "size",
+            value: size,
+        })?;
+        sizes.push(size);
+    }
+
+    let mut voxels = Vec::new();
+    let mut voxel = vec![0; dim];
+    for _ in 0..count {
+        let (_, _, birth) = tokens.next().ok_or(PerseusError::UnexpectedEnd)?;
+        if birth >= 0 {
+            voxels.push((voxel.clone(), birth as usize));
+        }
+        for (coord, size) in voxel.iter_mut().zip(sizes.iter()) {
+            *coord += 1;
+            if *coord < *size {
+                break;
+            }
+            *coord = 0;
+        }
+    }
+
+    Ok(voxel_filtration(&voxels)?)
+}
+
+/// Reads a sparse cubical complex in the Perseus format.
+///
+/// The first line is the dimension `d`. Each of the other lines is a voxel written as
+/// `x1 x2 ... xd b`, where `x1, ..., xd` are the coordinates of its lower corner and `b` is
+/// its birth time. The faces of the voxels are added and each of them is born with
+/// the earliest voxel containing it.
+pub fn read_perseus_cubical_sparse<R: BufRead>(
+    stream: R,
+) -> Result<CubicalFiltration<usize>, PerseusError> {
+    let mut lines = read_lines(stream)?.into_iter();
+    let dim = match lines.next() {
+        Some((line, numbers)) => {
+            check_length(line, &numbers, 1)?;
+            check_dimension(line, numbers[0])?
+        }
+        None => return Err(PerseusError::UnexpectedEnd),
+    };
+
+    let mut voxels = Vec::new();
+    for (line, numbers) in lines {
+        check_length(line, &numbers, dim + 1)?;
+        voxels.push((numbers[..dim].to_vec(), numbers[dim]));
+    }
+
+    Ok(voxel_filtration(&voxels)?)
+}
+
+/// Reads the non-blank lines of non-negative integers with their line numbers.
+fn read_lines<R: BufRead>(stream: R) -> Result<Vec<(usize, Vec<usize>)>, PerseusError> {
+    let mut lines = Vec::new();
+
+    for (index, line) in stream.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let numbers = line
+            .split_whitespace()
+            .map(|token| {
+                token.parse::<usize>().map_err(|_| PerseusError::Parse {
+                    line: index + 1,
+                    token: token.to_string(),
+                })
+            })
+            .collect::<Result<Vec<usize>, _>>()?;
+        lines.push((index + 1, numbers));
+    }
+
+    Ok(lines)
+}
+
+fn check_length(line: usize, numbers: &[usize], expected: usize) -> Result<(), PerseusError> {
+    if numbers.len() == expected {
+        Ok(())
+    } else {
+        Err(PerseusError::WrongLength {
+            line: line,
+            expected: expected,
+            found: numbers.len(),
+        })
+    }
+}
+
+fn check_dimension(line: usize, dim: usize) -> Result<usize, PerseusError> {
+    if dim <= MAX_DIMENSION {
+        Ok(dim)
+    } else {
+        Err(PerseusError::TooLarge {
+            line: line,
+            what: "dimension",
+            value: dim,
+        })
+    }
+}
+
+/// Records the birth time of a simplex, keeping the earliest one.
+fn insert_birth(births: &mut BTreeMap<Vec<usize>, usize>, simplex: Vec<usize>, birth: usize) {
+    let entry = births.entry(simplex).or_insert(birth);
+    if birth < *entry {
+        *entry = birth;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::reader::perseus::{
+        read_perseus_cubical_dense, read_perseus_cubical_sparse, read_perseus_simplicial,
+        PerseusError,
+    };
+    use crate::Persistence;
+
+    #[test]
+    fn test_read_perseus_simplicial() {
+        // The boundary of a triangle, filled in later.
+        let text = b"1\n1 0 1 1\n1 1 2 2\n1 0 2 3\n2 0 1 2 4\n";
+        let filt = read_perseus_simplicial(&text[..]).unwrap();
+        assert_eq!(filt.len(), 7);
+        assert_eq!(filt.values, vec![1, 1, 1, 2, 2, 3, 4]);
+
+        let diagram = filt.diagram().unwrap();
+        assert!(diagram.contains(&(1, Persistence(3, Some(4)))));
+
+        match read_perseus_simplicial(&b"1\n2 0 1 1\n"[..]) {
+            Err(PerseusError::WrongLength {
+                line: 2,
+                expected: 5,
+                found: 4,
+            }) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let huge = format!("1\n{} 0 1\n", usize::MAX);
+        match read_perseus_simplicial(huge.as_bytes()) {
+            Err(PerseusError::TooLarge { line: 2, .. }) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        let vertices = (0..64).map(|v| v.to_string()).collect::<Vec<_>>().join(" ");
+        let wide = format!("1\n63 {} 1\n", vertices);
+        match read_perseus_simplicial(wide.as_bytes()) {
+            Err(PerseusError::TooLarge { line: 2, .. }) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_read_perseus_cubical() {
+        // A 3x3 grid whose center is filled in at the end.
+        let dense = b"2\n3\n3\n1\n1\n1\n1\n5\n1\n1\n1\n1\n";
+        let filt = read_perseus_cubical_dense(&dense[..]).unwrap();
+        assert_eq!(filt.len(), 16 + 24 + 9);
+        let positive = filt
+            .diagram()
+            .into_iter()
+            .filter(|(_, Persistence(birth, death))| *death != Some(*birth))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            positive,
+            vec![(0, Persistence(1, None)), (1, Persistence(1, Some(5)))]
+        );
+
+        let sparse = b"2\n0 0 1\n1 0 1\n2 0 1\n0 1 1\n1 1 5\n2 1 1\n0 2 1\n1 2 1\n2 2 1\n";
+        let sparse = read_perseus_cubical_sparse(&sparse[..]).unwrap();
+        assert_eq!(sparse.values, filt.values);
+
+        let absent = read_perseus_cubical_dense(&b"2 2 1 -1 3"[..]).unwrap();
+        assert_eq!(absent.len(), 9);
+        assert!(read_perseus_cubical_dense(&b"2 2 1 3"[..]).is_err());
+
+        let huge = format!("3\n{}\n{}\n2\n", 1u64 << 40, 1u64 << 40);
+        match read_perseus_cubical_dense(huge.as_bytes()) {
+            Err(PerseusError::TooLarge { line: 3, .. }) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match read_perseus_cubical_dense(format!("{} 1", 1u64 << 40).as_bytes()) {
+            Err(PerseusError::TooLarge { line: 1, .. }) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match read_perseus_cubical_sparse(format!("{}\n0 1\n", usize::MAX).as_bytes()) {
+            Err(PerseusError::TooLarge { line: 1, .. }) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,359 @@
+use crate::boundary_matrix::BoundaryMatrix;
+use failure::Fail;
+use std::io::{BufRead, Read, Write};
+
+/// An error in reading a boundary matrix or persistence pairs in the PHAT formats
+///
+/// The line numbers start from one.
+#[derive(Debug, Fail)]
+pub enum PhatError {
+    #[fail(display = "I/O error: {}", _0)]
+    Io(#[cause] std::io::Error),
+    #[fail(display = "line {}: cannot parse {:?}", line, token)]
+    Parse { line: usize, token: String },
+    #[fail(display = "column {}: invalid value {}", column, value)]
+    InvalidValue { column: usize, value: i64 },
+    #[fail(
+        display = "column {}: row index {} is not less than the column index",
+        column, row
+    )]
+    NotTriangular { column: usize, row: usize },
+    #[fail(display = "line {}: expected {} numbers, found {}", line, expected, found)]
+    WrongLength {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    #[fail(display = "expected {} pairs, found {}", expected, found)]
+    WrongCount { expected: usize, found: usize },
+    #[fail(display = "pair {}: invalid value {}", pair, value)]
+    InvalidPairValue { pair: usize, value: i64 },
+    #[fail(display = "pair {}: birth {} is not less than death {}", pair, birth, death)]
+    NotOrdered {
+        pair: usize,
+        birth: usize,
+        death: usize,
+    },
+}
+
+impl From<std::io::Error> for PhatError {
+    fn from(e: std::io::Error) -> Self {
+        PhatError::Io(e)
+    }
+}
+
+/// Reads a boundary matrix in the ASCII format of PHAT.
+///
+/// Each line is a column, which lists the dimension and the row indices of the nonzero
+/// entries separated by whitespaces. Blank lines and the lines starting with `#` are ignored.
+pub fn read_phat_ascii<R: BufRead>(stream: R) -> Result<BoundaryMatrix, PhatError> {
+    let mut matrix = BoundaryMatrix::new();
+
+    for (index, line) in stream.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let numbers = line
+            .split_whitespace()
+            .map(|token| {
+                token.parse::<usize>().map_err(|_| PhatError::Parse {
+                    line: index + 1,
+                    token: token.to_string(),
+                })
+            })
+            .collect::<Result<Vec<usize>, _>>()?;
+        push_column(&mut matrix, numbers[0], numbers[1..].to_vec())?;
+    }
+
+    Ok(matrix)
+}
+
+/// Writes a boundary matrix in the ASCII format of PHAT.
+pub fn write_phat_ascii<W: Write>(writer: &mut W, matrix: &BoundaryMatrix) -> std::io::Result<()> {
+    writeln!(writer, "# dim idx0 idx1 ...")?;
+    for (dim, column) in matrix.dimensions.iter().zip(matrix.columns.iter()) {
+        write!(writer, "{}", dim)?;
+        for i in column.iter() {
+            write!(writer, " {}", i)?;
+        }
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+/// Reads a boundary matrix in the binary format of PHAT.
+///
+/// The format is a sequence of 64-bit little endian integers: the number of the columns
+/// followed by the dimension, the number of the nonzero entries and the row indices
+/// of each column.
+pub fn read_phat_binary<R: Read>(stream: &mut R) -> Result<BoundaryMatrix, PhatError> {
+    let mut matrix = BoundaryMatrix::new();
+
+    let count = read_i64(stream)?;
+    let count = to_usize(count, 0)?;
+    for column in 0..count {
+        let dim = to_usize(read_i64(stream)?, column)?;
+        let size = to_usize(read_i64(stream)?, column)?;
+        // The size may be larger than the file, so the column grows as it is read.
+        let mut rows = Vec::new();
+        for _ in 0..size {
+            rows.push(to_usize(read_i64(stream)?, column)?);
+        }
+        push_column(&mut matrix, dim, rows)?;
+    }
+
+    Ok(matrix)
+}
+
+/// Writes a boundary matrix in the binary format of PHAT.
+pub fn write_phat_binary<W: Write>(writer: &mut W, matrix: &BoundaryMatrix) -> std::io::Result<()> {
+    write_i64(writer, matrix.len() as i64)?;
+    for (dim, column) in matrix.dimensions.iter().zip(matrix.columns.iter()) {
+        write_i64(writer, *dim as i64)?;
+        write_i64(writer, column.len() as i64)?;
+        for i in column.iter() {
+            write_i64(writer, *i as i64)?;
+        }
+    }
+    Ok(())
+}
+
+/// Reads persistence pairs in the ASCII format of PHAT.
+///
+/// The first line is the number of the pairs, and each of the other lines is a pair of
+/// the birth and death column indices. Blank lines and the lines starting with `#` are
+/// ignored. The essential classes are not included in the format.
+pub fn read_phat_pairs_ascii<R: BufRead>(stream: R) -> Result<Vec<(usize, usize)>, PhatError> {
+    let mut count = None;
+    let mut pairs = Vec::new();
+
+    for (index, line) in stream.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let numbers = line
+            .split_whitespace()
+            .map(|token| {
+                token.parse::<usize>().map_err(|_| PhatError::Parse {
+                    line: index + 1,
+                    token: token.to_string(),
+                })
+            })
+            .collect::<Result<Vec<usize>, _>>()?;
+        let expected = if count.is_none() { 1 } else { 2 };
+        if numbers.len() != expected {
+            return Err(PhatError::WrongLength {
+                line: index + 1,
+                expected: expected,
+                found: numbers.len(),
+            });
+        }
+        match count {
+            None => count = Some(numbers[0]),
+            Some(_) => push_pair(&mut pairs, numbers[0], numbers[1])?,
+        }
+    }
+
+    let count = count.unwrap_or(0);
+    if pairs.len() != count {
+        return Err(PhatError::WrongCount {
+            expected: count,
+            found: pairs.len(),
+        });
+    }
+    Ok(pairs)
+}
+
+/// Writes persistence pairs in the ASCII format of PHAT.
+pub fn write_phat_pairs_ascii<W: Write>(
+    writer: &mut W,
+    pairs: &[(usize, usize)],
+) -> std::io::Result<()> {
+    writeln!(writer, "{}", pairs.len())?;
+    for (birth, death) in pairs.iter() {
+        writeln!(writer, "{} {}", birth, death)?;
+    }
+    Ok(())
+}
+
+/// Reads persistence pairs in the binary format of PHAT.
+///
+/// The format is a sequence of 64-bit little endian integers: the number of the pairs
+/// followed by the birth and death column indices of each pair.
+pub fn read_phat_pairs_binary<R: Read>(stream: &mut R) -> Result<Vec<(usize, usize)>, PhatError> {
+    let count = read_i64(stream)?;
+    let count = to_pair_index(count, 0)?;
+    // The count may be larger than the file, so the pairs grow as they are read.
+    let mut pairs = Vec::new();
+    for pair in 0..count {
+        let birth = to_pair_index(read_i64(stream)?, pair)?;
+        let death = to_pair_index(read_i64(stream)?, pair)?;
+        push_pair(&mut pairs, birth, death)?;
+    }
+    Ok(pairs)
+}
+
+/// Writes persistence pairs in the binary format of PHAT.
+pub fn write_phat_pairs_binary<W: Write>(
+    writer: &mut W,
+    pairs: &[(usize, usize)],
+) -> std::io::Result<()> {
+    write_i64(writer, pairs.len() as i64)?;
+    for (birth, death) in pairs.iter() {
+        write_i64(writer, *birth as i64)?;
+        write_i64(writer, *death as i64)?;
+    }
+    Ok(())
+}
+
+fn push_pair(pairs: &mut Vec<(usize, usize)>, birth: usize, death: usize) -> Result<(), PhatError> {
+    if birth >= death {
+        return Err(PhatError::NotOrdered {
+            pair: pairs.len(),
+            birth: birth,
+            death: death,
+        });
+    }
+    pairs.push((birth, death));
+    Ok(())
+}
+
+fn to_pair_index(value: i64, pair: usize) -> Result<usize, PhatError> {
+    if value < 0 {
+        Err(PhatError::InvalidPairValue {
+            pair: pair,
+            value: value,
+        })
+    } else {
+        Ok(value as usize)
+    }
+}
+
+fn push_column(matrix: &mut BoundaryMatrix, dim: usize, rows: Vec<usize>) -> Result<(), PhatError> {
+    let column = matrix.len();
+    if let Some(row) = rows.iter().find(|row| **row >= column) {
+        return Err(PhatError::NotTriangular {
+            column: column,
+            row: *row,
+        });
+    }
+    matrix.push(dim, rows);
+    Ok(())
+}
+
+fn to_usize(value: i64, column: usize) -> Result<usize, PhatError> {
+    if value < 0 {
+        Err(PhatError::InvalidValue {
+            column: column,
+            value: value,
+        })
+    } else {
+        Ok(value as usize)
+    }
+}
+
+pub(crate) fn read_i64<R: Read>(stream: &mut R) -> std::io::Result<i64> {
+    let mut bytes = [0; 8];
+    stream.read_exact(&mut bytes)?;
+    Ok(i64::from_le_bytes(bytes))
+}
+
+pub(crate) fn write_i64<W: Write>(writer: &mut W, value: i64) -> std::io::Result<()> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::reader::phat::{
+        read_phat_ascii, read_phat_binary, read_phat_pairs_ascii, read_phat_pairs_binary,
+        write_phat_ascii, write_phat_binary, write_phat_pairs_ascii, write_phat_pairs_binary,
+        PhatError,
+    };
+    use crate::Persistence;
+
+    #[test]
+    fn test_phat_formats() {
+        let text = b"# a triangle\n0\n0\n0\n1 0 1\n1 1 2\n1 0 2\n2 3 4 5\n";
+        let matrix = read_phat_ascii(&text[..]).unwrap();
+        assert_eq!(matrix.len(), 7);
+        assert_eq!(matrix.columns[6], vec![3, 4, 5]);
+
+        let mut ascii = Vec::new();
+        write_phat_ascii(&mut ascii, &matrix).unwrap();
+        assert_eq!(read_phat_ascii(&ascii[..]).unwrap(), matrix);
+
+        let mut binary = Vec::new();
+        write_phat_binary(&mut binary, &matrix).unwrap();
+        assert_eq!(binary.len(), 8 * (1 + 2 * 7 + 2 * 3 + 3));
+        assert_eq!(read_phat_binary(&mut &binary[..]).unwrap(), matrix);
+        assert!(read_phat_binary(&mut &binary[..binary.len() - 1]).is_err());
+
+        match read_phat_ascii(&b"0\n1 0 1\n"[..]) {
+            Err(PhatError::NotTriangular { column: 1, row: 1 }) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_phat_truncated() {
+        let mut bytes = Vec::new();
+        for value in [i64::MAX, 0, i64::MAX, 0].iter() {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        match read_phat_binary(&mut &bytes[..]) {
+            Err(PhatError::Io(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_phat_pairs() {
+        let text = b"0\n0\n0\n1 0 1\n1 1 2\n1 0 2\n2 3 4 5\n";
+        let pairs = read_phat_ascii(&text[..])
+            .unwrap()
+            .pairs()
+            .into_iter()
+            .filter_map(|(_, Persistence(birth, death))| death.map(|death| (birth, death)))
+            .collect::<Vec<_>>();
+        assert_eq!(pairs, vec![(1, 3), (2, 4), (5, 6)]);
+
+        let mut ascii = Vec::new();
+        write_phat_pairs_ascii(&mut ascii, &pairs).unwrap();
+        assert_eq!(ascii, b"3\n1 3\n2 4\n5 6\n".to_vec());
+        assert_eq!(read_phat_pairs_ascii(&ascii[..]).unwrap(), pairs);
+
+        let mut binary = Vec::new();
+        write_phat_pairs_binary(&mut binary, &pairs).unwrap();
+        assert_eq!(binary.len(), 8 * (1 + 2 * 3));
+        assert_eq!(read_phat_pairs_binary(&mut &binary[..]).unwrap(), pairs);
+        assert!(read_phat_pairs_binary(&mut &binary[..binary.len() - 1]).is_err());
+
+        match read_phat_pairs_ascii(&b"2\n1 3\n"[..]) {
+            Err(PhatError::WrongCount {
+                expected: 2,
+                found: 1,
+            }) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match read_phat_pairs_ascii(&b"1\n3 1\n"[..]) {
+            Err(PhatError::NotOrdered { pair: 0, .. }) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match read_phat_pairs_ascii(&b"1\n1 3 4\n"[..]) {
+            Err(PhatError::WrongLength { line: 2, .. }) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        let mut bytes = Vec::new();
+        for value in [1, -1, 3].iter() {
+            bytes.extend_from_slice(&(*value as i64).to_le_bytes());
+        }
+        match read_phat_pairs_binary(&mut &bytes[..]) {
+            Err(PhatError::InvalidPairValue { pair: 0, value: -1 }) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
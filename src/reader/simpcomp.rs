@@ -1,26 +1,500 @@
-use botao::text::{DataRecordReaderBuilder, DataBlockReader};
 use crate::complex::Complex;
-use crate::simplex::Simplex;
+use crate::filtration::Filtration;
 use crate::indexed_vec::IndexedVec;
+use crate::simplex::Simplex;
+use crate::traits::*;
+use botao::text::{DataRecord, DataRecordReaderBuilder, ReaderError};
+use failure::Fail;
+use std::collections::{BTreeMap, BTreeSet};
 use std::io::BufRead;
 
-pub fn read_simpcomp_text<R: BufRead>(stream: R) -> Option<Complex<IndexedVec<Simplex>, Simplex>> {
-    let block = {
-        let reader = DataRecordReaderBuilder::new()
-            .record_delimiter(b'\n')
-            .field_delimiter(b' ')
-            .build(stream);
-        let mut reader = DataBlockReader::<usize, _>::new(reader);
+/// An error in reading a simplicial complex
+///
+/// The line numbers start from one.
+#[derive(Debug, Fail)]
+pub enum SimpcompError {
+    #[fail(display = "I/O error: {}", _0)]
+    Io(#[cause] std::io::Error),
+    #[fail(display = "line {}: cannot parse {:?}", line, token)]
+    Parse { line: usize, token: String },
+    #[fail(display = "line {}: simplex has no vertices", line)]
+    EmptySimplex { line: usize },
+    #[fail(display = "line {}: vertex {} is repeated", line, vertex)]
+    RepeatedVertex { line: usize, vertex: usize },
+    #[fail(display = "line {}: simplex {} already exists", line, simplex)]
+    DuplicateSimplex { line: usize, simplex: Simplex },
+    #[fail(display = "line {}: face {} of simplex {} is missing", line, face, simplex)]
+    MissingFace {
+        line: usize,
+        simplex: Simplex,
+        face: Simplex,
+    },
+    #[fail(
+        display = "line {}: face {} has a larger value than simplex {}",
+        line, face, simplex
+    )]
+    FaceAfterCoface {
+        line: usize,
+        simplex: Simplex,
+        face: Simplex,
+    },
+    #[fail(display = "line {}: facet has too many vertices ({})", line, count)]
+    TooManyVertices { line: usize, count: usize },
+}
 
-        reader.next_block().unwrap().unwrap()
-    };
+/// The position of the filtration value in a line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValuePosition {
+    /// The line is `value v0 v1 ... vk`.
+    First,
+    /// The line is `v0 v1 ... vk value`.
+    Last,
+}
+
+impl std::str::FromStr for ValuePosition {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "first" => Ok(ValuePosition::First),
+            "last" => Ok(ValuePosition::Last),
+            _ => Err(format!("unknown value position: {}", s)),
+        }
+    }
+}
+
+impl From<std::io::Error> for SimpcompError {
+    fn from(e: std::io::Error) -> Self {
+        SimpcompError::Io(e)
+    }
+}
+
+/// Reads a simplicial complex.
+///
+/// Each line lists the vertices of a simplex separated by whitespaces, and the faces of
+/// a simplex must be listed before it. The simplices are read up to the first blank line
+/// following a simplex. The lines starting with `#` are comments.
+pub fn read_simpcomp_text<R: BufRead>(
+    stream: R,
+) -> Result<Complex<IndexedVec<Simplex>, Simplex>, SimpcompError> {
+    let mut complex = Complex::<IndexedVec<Simplex>, Simplex>::new();
+    let mut index = BTreeSet::new();
+
+    for (line, v) in read_blocks(stream, false)?.into_iter().flatten() {
+        complex.push_unchecked(check_simplex(&mut index, line, v)?);
+    }
+
+    Ok(complex)
+}
+
+/// Reads a simplicial complex whose blocks are the stages of a filtration.
+///
+/// The blocks are separated by blank lines and the `i`-th block lists the simplices added
+/// at the `i`-th stage in the format of `read_simpcomp_text`. The complex of each stage
+/// is created by `Complex::with_prev` from the previous one, so the indices of the simplices
+/// run through all the stages. The faces of a simplex may be in the same or earlier stages.
+pub fn read_simpcomp_stages_text<R: BufRead>(
+    stream: R,
+) -> Result<Vec<Complex<IndexedVec<Simplex>, Simplex>>, SimpcompError> {
+    let mut stages: Vec<Complex<IndexedVec<Simplex>, Simplex>> = Vec::new();
+    let mut index = BTreeSet::new();
+
+    for block in read_blocks(stream, true)?.into_iter() {
+        let mut complex = match stages.last() {
+            Some(prev) => Complex::with_prev(prev),
+            None => Complex::new(),
+        };
+        for (line, v) in block.into_iter() {
+            complex.push_unchecked(check_simplex(&mut index, line, v)?);
+        }
+        stages.push(complex);
+    }
+
+    Ok(stages)
+}
+
+/// Reads a simplicial complex whose blocks are the stages of a filtration as a filtration.
+///
+/// The filtration value of a simplex is the index of its stage.
+/// See `read_simpcomp_stages_text` for the format.
+pub fn read_simpcomp_filtration_text<R: BufRead>(
+    stream: R,
+) -> Result<Filtration<Simplex, usize>, SimpcompError> {
+    let mut filt = Filtration::new();
+
+    for (stage, complex) in read_simpcomp_stages_text(stream)?.iter().enumerate() {
+        for (_, simplex) in complex.basis.iter() {
+            filt.push_unchecked(simplex.clone(), stage);
+        }
+    }
+
+    Ok(filt)
+}
+
+/// Checks that the simplex of `vertices` is not in `index` and its faces are, and adds it
+/// to `index`.
+///
+/// `index` holds the sorted vertices of the simplices read so far.
+fn check_simplex(
+    index: &mut BTreeSet<Vec<usize>>,
+    line: usize,
+    mut vertices: Vec<usize>,
+) -> Result<Simplex, SimpcompError> {
+    vertices.sort();
+    check_vertices(line, &vertices)?;
+
+    if index.contains(&vertices) {
+        return Err(SimpcompError::DuplicateSimplex {
+            line: line,
+            simplex: Simplex::new(vertices),
+        });
+    }
+    if vertices.len() > 1 {
+        for i in 0..vertices.len() {
+            let mut face = vertices.clone();
+            face.remove(i);
+            if !index.contains(&face) {
+                return Err(SimpcompError::MissingFace {
+                    line: line,
+                    simplex: Simplex::new(vertices),
+                    face: Simplex::new(face),
+                });
+            }
+        }
+    }
+
+    index.insert(vertices.clone());
+    Ok(Simplex::new(vertices))
+}
+
+/// Checks that the sorted `vertices` are not empty and have no repeated vertex.
+fn check_vertices(line: usize, vertices: &[usize]) -> Result<(), SimpcompError> {
+    if vertices.is_empty() {
+        return Err(SimpcompError::EmptySimplex { line: line });
+    }
+    match vertices.windows(2).find(|w| w[0] == w[1]) {
+        Some(w) => Err(SimpcompError::RepeatedVertex {
+            line: line,
+            vertex: w[0],
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Reads a filtered simplicial complex whose simplices have filtration values.
+///
+/// Each line lists the filtration value and the vertices of a simplex separated by
+/// whitespaces, where the value is at the position `position`. Blank lines are ignored and
+/// the simplices may be in any order. The simplices are sorted by the value and then by
+/// the dimension, so the value of a simplex must not be less than the values of its faces.
+pub fn read_simpcomp_values_text<R: BufRead>(
+    stream: R,
+    position: ValuePosition,
+) -> Result<Filtration<Simplex, f64>, SimpcompError> {
+    let mut simplices = BTreeMap::new();
+
+    for (index, line) in stream.lines().enumerate() {
+        let line = line?;
+        let mut tokens: Vec<&str> = line.split_whitespace().collect();
+        if tokens.is_empty() {
+            continue;
+        }
+        let value = match position {
+            ValuePosition::First => tokens.remove(0),
+            ValuePosition::Last => tokens.pop().unwrap(),
+        };
+        let value = match value.parse::<f64>() {
+            Ok(v) if !v.is_nan() => v,
+            _ => {
+                return Err(SimpcompError::Parse {
+                    line: index + 1,
+                    token: value.to_string(),
+                })
+            }
+        };
+        let mut vertices = tokens
+            .into_iter()
+            .map(|token| {
+                token.parse::<usize>().map_err(|_| SimpcompError::Parse {
+                    line: index + 1,
+                    token: token.to_string(),
+                })
+            })
+            .collect::<Result<Vec<usize>, _>>()?;
+        vertices.sort();
+        check_vertices(index + 1, &vertices)?;
+        if simplices.contains_key(&vertices) {
+            return Err(SimpcompError::DuplicateSimplex {
+                line: index + 1,
+                simplex: Simplex::new(vertices),
+            });
+        }
+        simplices.insert(vertices, (index + 1, value));
+    }
+
+    for (vertices, (line, value)) in simplices.iter() {
+        if vertices.len() < 2 {
+            continue;
+        }
+        for i in 0..vertices.len() {
+            let mut face = vertices.clone();
+            face.remove(i);
+            match simplices.get(&face) {
+                None => {
+                    return Err(SimpcompError::MissingFace {
+                        line: *line,
+                        simplex: Simplex::new(vertices.clone()),
+                        face: Simplex::new(face),
+                    });
+                }
+                Some((_, face_value)) if face_value > value => {
+                    return Err(SimpcompError::FaceAfterCoface {
+                        line: *line,
+                        simplex: Simplex::new(vertices.clone()),
+                        face: Simplex::new(face),
+                    });
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let mut elems: Vec<(usize, Simplex, f64)> = simplices
+        .into_iter()
+        .map(|(vertices, (line, value))| (line, Simplex::new(vertices), value))
+        .collect();
+    elems.sort_by_key(|(line, _, _)| *line);
+
+    Ok(Filtration::from_unsorted(
+        elems
+            .into_iter()
+            .map(|(_, simplex, value)| (simplex, value))
+            .collect(),
+    ))
+}
+
+/// The largest dimension of the facets read by `read_simpcomp_facets_text`
+///
+/// A facet of dimension `d` has `2^(d+1) - 1` faces, so larger ones are not practical.
+pub const MAX_FACET_DIMENSION: usize = 20;
+
+/// Reads a complex given by its maximal simplices.
+///
+/// Each line lists the vertices of a facet and the facets are read up to the first blank line
+/// following a facet, as in `read_simpcomp_text`. The complex consists of the facets and all
+/// their faces, which need not be listed, and its simplices are ordered by dimension.
+/// The facets of dimension larger than `MAX_FACET_DIMENSION` are rejected.
+pub fn read_simpcomp_facets_text<R: BufRead>(
+    stream: R,
+) -> Result<Complex<IndexedVec<Simplex>, Simplex>, SimpcompError> {
+    let mut simplices = BTreeSet::new();
+    for (line, mut facet) in read_blocks(stream, false)?.into_iter().flatten() {
+        facet.sort();
+        check_vertices(line, &facet)?;
+        let n = facet.len();
+        if n > MAX_FACET_DIMENSION + 1 {
+            return Err(SimpcompError::TooManyVertices {
+                line: line,
+                count: n,
+            });
+        }
+        for mask in 1..1usize << n {
+            let face: Vec<usize> = (0..n)
+                .filter(|i| mask & (1 << i) != 0)
+                .map(|i| facet[i])
+                .collect();
+            simplices.insert((face.len() - 1, face));
+        }
+    }
 
     let mut complex = Complex::new();
 
-    for v in block.into_iter() {
-        let simplex = Simplex::new(v);
-        complex.push(simplex).unwrap();
+    for (_, v) in simplices.into_iter() {
+        complex.push_unchecked(Simplex::new(v));
     }
 
-    Some(complex)
+    Ok(complex)
+}
+
+/// The lines of a block with their line numbers
+type Block = Vec<(usize, Vec<usize>)>;
+
+/// Reads the blocks of lines separated by blank lines.
+///
+/// All the blocks are read if `all` is true and only the first one otherwise. The records
+/// are read one by one rather than by `DataBlockReader` to keep track of the line numbers.
+fn read_blocks<R: BufRead>(stream: R, all: bool) -> Result<Vec<Block>, SimpcompError> {
+    let mut reader = DataRecordReaderBuilder::new()
+        .record_delimiter(b'\n')
+        .field_delimiter(b' ')
+        .build(stream);
+    let mut blocks = Vec::new();
+    let mut block = Vec::new();
+    let mut line = 0;
+
+    loop {
+        line += 1;
+        match reader.next_record().map_err(reader_error)? {
+            DataRecord::Fields(fields) => {
+                let vertices = fields
+                    .iter()
+                    .flat_map(|field| field.split_whitespace())
+                    .map(|token| {
+                        token.parse::<usize>().map_err(|_| SimpcompError::Parse {
+                            line: line,
+                            token: token.to_string(),
+                        })
+                    })
+                    .collect::<Result<Vec<usize>, _>>()?;
+                block.push((line, vertices));
+            }
+            DataRecord::Comment(_) => {}
+            DataRecord::Blank => {
+                if !block.is_empty() {
+                    blocks.push(block);
+                    block = Vec::new();
+                    if !all {
+                        break;
+                    }
+                }
+            }
+            DataRecord::EOF => break,
+        }
+    }
+    if !block.is_empty() {
+        blocks.push(block);
+    }
+
+    Ok(blocks)
+}
+
+/// Converts an error of `DataRecordReader` into `SimpcompError::Io`.
+fn reader_error(e: failure::Error) -> SimpcompError {
+    match e.downcast::<ReaderError>() {
+        Ok(ReaderError::Io(e)) => SimpcompError::Io(e),
+        Ok(ReaderError::FromUTF8(e)) => {
+            SimpcompError::Io(std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+        }
+        Err(e) => SimpcompError::Io(std::io::Error::other(e.compat())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::reader::simpcomp::{
+        read_simpcomp_facets_text, read_simpcomp_filtration_text, read_simpcomp_stages_text,
+        read_simpcomp_text, read_simpcomp_values_text, SimpcompError, ValuePosition,
+    };
+    use crate::Persistence;
+    use crate::simplex;
+    use crate::traits::*;
+
+    #[test]
+    fn test_read_simpcomp_text() {
+        let complex = read_simpcomp_text(&b"\n0\n1\n2\n0 1\n1 2\n0 2\n\n0 1 2\n"[..]).unwrap();
+        assert_eq!(complex.basis.len(), 6);
+
+        let input = b"# triangle\n0\n1\n2\n0 1\n1 2\n0 2\n0 1 2\n";
+        let complex = read_simpcomp_text(&input[..]).unwrap();
+        assert_eq!(complex.basis.len(), 7);
+
+        let complex = read_simpcomp_facets_text(&b"0 1 2\n2 3\n"[..]).unwrap();
+        assert_eq!(complex.basis.len(), 7 + 2);
+    }
+
+    #[test]
+    fn test_read_simpcomp_text_errors() {
+        match read_simpcomp_text(&b"0\n1\n0 x\n"[..]) {
+            Err(SimpcompError::Parse { line: 3, token }) => assert_eq!(token, "x"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match read_simpcomp_text(&b"0\n1\n1 0\n0 1\n"[..]) {
+            Err(SimpcompError::DuplicateSimplex { line: 4, simplex }) => {
+                assert_eq!(simplex, simplex![0, 1])
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match read_simpcomp_text(&b"0\n1\n0 2\n"[..]) {
+            Err(SimpcompError::MissingFace { line: 3, face, .. }) => assert_eq!(face, simplex![2]),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match read_simpcomp_text(&b"0\n0 0\n"[..]) {
+            Err(SimpcompError::RepeatedVertex { line: 2, vertex: 0 }) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match read_simpcomp_stages_text(&b"0\n1\n\n1 0 1\n"[..]) {
+            Err(SimpcompError::RepeatedVertex { line: 4, vertex: 1 }) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match read_simpcomp_values_text(&b"0 0\n1 0 0\n"[..], ValuePosition::First) {
+            Err(SimpcompError::RepeatedVertex { line: 2, vertex: 0 }) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(read_simpcomp_facets_text(&b"0 1\n-1 2\n"[..]).is_err());
+        let wide = (0..40).map(|v| v.to_string()).collect::<Vec<_>>().join(" ");
+        match read_simpcomp_facets_text(format!("0 1\n{}\n", wide).as_bytes()) {
+            Err(SimpcompError::TooManyVertices { line: 2, count: 40 }) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match read_simpcomp_facets_text(&b"0 1\n2 1 2\n"[..]) {
+            Err(SimpcompError::RepeatedVertex { line: 2, vertex: 2 }) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_read_simpcomp_stages_text() {
+        let text = b"0\n1\n2\n\n0 1\n1 2\n0 2\n\n\n0 1 2\n";
+        let stages = read_simpcomp_stages_text(&text[..]).unwrap();
+        assert_eq!(stages.len(), 3);
+        assert_eq!(stages[1].basis.index_range(), 3..6);
+        assert_eq!(stages[2].basis.index_range(), 6..7);
+
+        let filt = read_simpcomp_filtration_text(&text[..]).unwrap();
+        assert_eq!(filt.values, vec![0, 0, 0, 1, 1, 1, 2]);
+        let diagram = filt.diagram().unwrap();
+        assert!(diagram.contains(&(0, Persistence(0, None))));
+        assert!(diagram.contains(&(1, Persistence(1, Some(2)))));
+
+        match read_simpcomp_stages_text(&b"0\n\n0 1\n"[..]) {
+            Err(SimpcompError::MissingFace { line: 3, face, .. }) => assert_eq!(face, simplex![1]),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_read_simpcomp_values_text() {
+        let text = b"0.75 0 1 2\n0 0\n0.25 1\n0.5 2\n0.5 0 1\n0.5 1 2\n0.75 0 2\n";
+        let filt = read_simpcomp_values_text(&text[..], ValuePosition::First).unwrap();
+        let elems: Vec<_> = filt
+            .complex
+            .basis
+            .iter()
+            .map(|(index, s)| (s.clone(), filt.values[index]))
+            .collect();
+        assert_eq!(
+            elems,
+            vec![
+                (simplex![0], 0.0),
+                (simplex![1], 0.25),
+                (simplex![2], 0.5),
+                (simplex![0, 1], 0.5),
+                (simplex![1, 2], 0.5),
+                (simplex![0, 2], 0.75),
+                (simplex![0, 1, 2], 0.75),
+            ]
+        );
+
+        let text = b"0 0\n1 0.5\n0 1 0.25\n";
+        match read_simpcomp_values_text(&text[..], ValuePosition::Last) {
+            Err(SimpcompError::FaceAfterCoface { line: 3, face, .. }) => {
+                assert_eq!(face, simplex![1])
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match read_simpcomp_values_text(&b"0.5\n"[..], ValuePosition::First) {
+            Err(SimpcompError::EmptySimplex { line: 1 }) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
 }
@@ -0,0 +1,396 @@
+use crate::cube::{
+    lower_star_grid_filtration, voxel_grid_filtration, CubeError, CubicalFiltration,
+};
+use failure::Fail;
+use std::io::Read;
+
+/// An error in reading a volume
+#[derive(Debug, Fail)]
+pub enum VolumeError {
+    #[fail(display = "I/O error: {}", _0)]
+    Io(#[cause] std::io::Error),
+    #[fail(display = "not a NumPy array file")]
+    NotNpy,
+    #[fail(display = "invalid header {:?}", _0)]
+    InvalidHeader(String),
+    #[fail(display = "unsupported data type {:?}", _0)]
+    UnsupportedType(String),
+    #[fail(display = "the data is longer than the shape")]
+    TrailingData,
+    #[fail(display = "the shape {:?} is too large", _0)]
+    TooLarge(Vec<usize>),
+    #[fail(display = "element {} is not a number", _0)]
+    NotANumber(usize),
+}
+
+impl From<std::io::Error> for VolumeError {
+    fn from(e: std::io::Error) -> Self {
+        VolumeError::Io(e)
+    }
+}
+
+/// The data type of the elements of a volume
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataType {
+    Bool,
+    Int8,
+    UInt8,
+    Int16,
+    UInt16,
+    Int32,
+    UInt32,
+    Int64,
+    UInt64,
+    Float32,
+    Float64,
+}
+
+impl DataType {
+    /// Returns the size of an element in bytes.
+    pub fn size(self) -> usize {
+        match self {
+            DataType::Bool | DataType::Int8 | DataType::UInt8 => 1,
+            DataType::Int16 | DataType::UInt16 => 2,
+            DataType::Int32 | DataType::UInt32 | DataType::Float32 => 4,
+            DataType::Int64 | DataType::UInt64 | DataType::Float64 => 8,
+        }
+    }
+
+    /// Decodes an element from its bytes.
+    fn decode(self, bytes: &[u8], endianness: Endianness) -> f64 {
+        let mut b = [0; 8];
+        b[..bytes.len()].copy_from_slice(bytes);
+        if endianness == Endianness::Big {
+            b[..bytes.len()].reverse();
+        }
+        let b4 = [b[0], b[1], b[2], b[3]];
+        match self {
+            DataType::Bool => {
+                if b[0] != 0 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            DataType::Int8 => f64::from(b[0] as i8),
+            DataType::UInt8 => f64::from(b[0]),
+            DataType::Int16 => f64::from(i16::from_le_bytes([b[0], b[1]])),
+            DataType::UInt16 => f64::from(u16::from_le_bytes([b[0], b[1]])),
+            DataType::Int32 => f64::from(i32::from_le_bytes(b4)),
+            DataType::UInt32 => f64::from(u32::from_le_bytes(b4)),
+            DataType::Int64 => i64::from_le_bytes(b) as f64,
+            DataType::UInt64 => u64::from_le_bytes(b) as f64,
+            DataType::Float32 => f64::from(f32::from_bits(u32::from_le_bytes(b4))),
+            DataType::Float64 => f64::from_bits(u64::from_le_bytes(b)),
+        }
+    }
+}
+
+impl std::str::FromStr for DataType {
+    type Err = String;
+
+    /// Parses the type code of NumPy such as `f4` or the name such as `float32`.
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "b1" | "?" | "bool" => Ok(DataType::Bool),
+            "i1" | "int8" => Ok(DataType::Int8),
+            "u1" | "uint8" => Ok(DataType::UInt8),
+            "i2" | "int16" => Ok(DataType::Int16),
+            "u2" | "uint16" => Ok(DataType::UInt16),
+            "i4" | "int32" => Ok(DataType::Int32),
+            "u4" | "uint32" => Ok(DataType::UInt32),
+            "i8" | "int64" => Ok(DataType::Int64),
+            "u8" | "uint64" => Ok(DataType::UInt64),
+            "f4" | "float32" => Ok(DataType::Float32),
+            "f8" | "float64" => Ok(DataType::Float64),
+            _ => Err(format!("unknown data type: {}", s)),
+        }
+    }
+}
+
+/// The byte order of the elements of a volume
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endianness {
+    Little,
+    Big,
+}
+
+impl std::str::FromStr for Endianness {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "little" => Ok(Endianness::Little),
+            "big" => Ok(Endianness::Big),
+            _ => Err(format!("unknown endianness: {}", s)),
+        }
+    }
+}
+
+/// Multidimensional array of scalar values
+///
+/// The `values` are stored in row-major order, i.e. the last coordinate changes fastest.
+/// The integers are converted to `f64`, so those greater than `2^53` may be rounded.
+/// The filtrations can be built only if the volume has at least one axis.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Volume {
+    pub shape: Vec<usize>,
+    pub values: Vec<f64>,
+}
+
+impl Volume {
+    /// Builds the cubical filtration whose voxels are the elements of the volume.
+    ///
+    /// Each face of the voxels has the least value of the voxels containing it.
+    pub fn voxel_filtration(&self) -> Result<CubicalFiltration<f64>, CubeError> {
+        voxel_grid_filtration(&self.shape, &self.values)
+    }
+
+    /// Builds the lower-star filtration of the cubical grid whose vertices are the elements
+    /// of the volume.
+    pub fn lower_star_filtration(&self) -> Result<CubicalFiltration<f64>, CubeError> {
+        lower_star_grid_filtration(&self.shape, &self.values)
+    }
+}
+
+/// Reads an array in the NumPy format (`.npy`).
+///
+/// The arrays of booleans, integers and floating point numbers are supported in both
+/// C and Fortran orders.
+pub fn read_npy<R: Read>(mut stream: R) -> Result<Volume, VolumeError> {
+    let mut magic = [0; 8];
+    stream.read_exact(&mut magic)?;
+    if &magic[..6] != b"\x93NUMPY" {
+        return Err(VolumeError::NotNpy);
+    }
+    let header_len = if magic[6] == 1 {
+        let mut bytes = [0; 2];
+        stream.read_exact(&mut bytes)?;
+        u16::from_le_bytes(bytes) as usize
+    } else {
+        let mut bytes = [0; 4];
+        stream.read_exact(&mut bytes)?;
+        u32::from_le_bytes(bytes) as usize
+    };
+    let mut header = vec![0; header_len];
+    stream.read_exact(&mut header)?;
+    let header = String::from_utf8_lossy(&header).into_owned();
+    let invalid = || VolumeError::InvalidHeader(header.clone());
+
+    let descr = header_value(&header, "descr").ok_or_else(invalid)?;
+    let descr = descr
+        .trim_start_matches(&['\'', '"'][..])
+        .split(&['\'', '"'][..])
+        .next()
+        .ok_or_else(invalid)?;
+    let (endianness, code) = match descr.chars().next() {
+        Some('>') => (Endianness::Big, &descr[1..]),
+        Some('<') | Some('|') | Some('=') => (Endianness::Little, &descr[1..]),
+        _ => (Endianness::Little, descr),
+    };
+    let dtype = code
+        .parse::<DataType>()
+        .map_err(|_| VolumeError::UnsupportedType(descr.to_string()))?;
+
+    let fortran_order = header_value(&header, "fortran_order")
+        .ok_or_else(invalid)?
+        .starts_with("True");
+
+    let shape = header_value(&header, "shape").ok_or_else(invalid)?;
+    let shape = shape
+        .trim_start_matches('(')
+        .split(')')
+        .next()
+        .ok_or_else(invalid)?
+        .split(',')
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse::<usize>().map_err(|_| invalid()))
+        .collect::<Result<Vec<usize>, _>>()?;
+
+    if fortran_order {
+        let mut reversed = shape.clone();
+        reversed.reverse();
+        let transposed = read_raw(stream, dtype, endianness, &reversed)?;
+        Ok(Volume {
+            values: transpose(&transposed.values, &shape),
+            shape: shape,
+        })
+    } else {
+        read_raw(stream, dtype, endianness, &shape)
+    }
+}
+
+/// Reads a raw binary volume.
+///
+/// The elements are stored in row-major order without any header.
+/// The NaN elements are rejected since the filtrations cannot be built from them.
+pub fn read_raw<R: Read>(
+    mut stream: R,
+    dtype: DataType,
+    endianness: Endianness,
+    shape: &[usize],
+) -> Result<Volume, VolumeError> {
+    let size = dtype.size();
+    let len = shape
+        .iter()
+        .try_fold(size, |len, axis| len.checked_mul(*axis))
+        .ok_or_else(|| VolumeError::TooLarge(shape.to_vec()))?;
+
+    // The buffer grows with the data read, so a large shape of a short file fails
+    // at the end of the data.
+    let mut bytes = Vec::new();
+    stream.by_ref().take(len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() < len {
+        return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+    }
+    if stream.read(&mut [0])? != 0 {
+        return Err(VolumeError::TrailingData);
+    }
+
+    let values: Vec<f64> = bytes
+        .chunks(size)
+        .map(|chunk| dtype.decode(chunk, endianness))
+        .collect();
+    if let Some(index) = values.iter().position(|value| value.is_nan()) {
+        return Err(VolumeError::NotANumber(index));
+    }
+    Ok(Volume {
+        shape: shape.to_vec(),
+        values: values,
+    })
+}
+
+/// Finds the text of the value of the key in the header of a NumPy array.
+fn header_value<'a>(header: &'a str, key: &str) -> Option<&'a str> {
+    ["'", "\""].iter().find_map(|quote| {
+        let pattern = format!("{}{}{}", quote, key, quote);
+        let start = header.find(&pattern)? + pattern.len();
+        let rest = header[start..].trim_start();
+        let mut parts = rest.splitn(2, ':');
+        match (parts.next(), parts.next()) {
+            (Some(""), Some(value)) => Some(value.trim_start()),
+            _ => None,
+        }
+    })
+}
+
+/// Converts the values in column-major order to those in row-major order.
+fn transpose(values: &[f64], shape: &[usize]) -> Vec<f64> {
+    let mut transposed = Vec::with_capacity(values.len());
+    let mut coords = vec![0; shape.len()];
+    for _ in 0..values.len() {
+        let mut index = 0;
+        for (coord, size) in coords.iter().zip(shape.iter()).rev() {
+            index = index * size + coord;
+        }
+        transposed.push(values[index]);
+        for (coord, size) in coords.iter_mut().zip(shape.iter()).rev() {
+            *coord += 1;
+            if *coord < *size {
+                break;
+            }
+            *coord = 0;
+        }
+    }
+    transposed
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::reader::volume::{read_npy, read_raw, DataType, Endianness, Volume, VolumeError};
+    use crate::Persistence;
+
+    fn npy(descr: &str, fortran_order: bool, shape: &str, data: &[u8]) -> Vec<u8> {
+        let mut header = format!(
+            "{{'descr': '{}', 'fortran_order': {}, 'shape': {}, }}",
+            descr,
+            if fortran_order { "True" } else { "False" },
+            shape
+        );
+        while (10 + header.len() + 1) % 64 != 0 {
+            header.push(' ');
+        }
+        header.push('\n');
+        let mut bytes = b"\x93NUMPY\x01\x00".to_vec();
+        bytes.extend_from_slice(&(header.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(header.as_bytes());
+        bytes.extend_from_slice(data);
+        bytes
+    }
+
+    #[test]
+    fn test_read_npy() {
+        let data = (0..6u16)
+            .flat_map(|i| i.to_be_bytes().to_vec())
+            .collect::<Vec<u8>>();
+        let volume = read_npy(&npy(">u2", false, "(2, 3)", &data)[..]).unwrap();
+        assert_eq!(volume.shape, vec![2, 3]);
+        assert_eq!(volume.values, vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0]);
+
+        let data = [0.0f64, 3.0, 1.0, 4.0, 2.0, 5.0]
+            .iter()
+            .flat_map(|x| x.to_bits().to_le_bytes().to_vec())
+            .collect::<Vec<u8>>();
+        assert_eq!(
+            read_npy(&npy("<f8", true, "(2, 3)", &data)[..]).unwrap(),
+            volume
+        );
+
+        match read_npy(&npy("<c16", false, "(1,)", &[0; 16])[..]) {
+            Err(VolumeError::UnsupportedType(ref descr)) if descr == "<c16" => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(read_npy(&b"P5\n3 3\n255\n"[..]).is_err());
+
+        let huge = format!("({}, {})", 1u64 << 40, 1u64 << 40);
+        match read_npy(&npy("<f8", false, &huge, &[0; 16])[..]) {
+            Err(VolumeError::TooLarge(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        let large = format!("({},)", 1u64 << 40);
+        match read_npy(&npy("<f8", false, &large, &[0; 16])[..]) {
+            Err(VolumeError::Io(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        let data = [0.0f32, f32::NAN]
+            .iter()
+            .flat_map(|x| x.to_bits().to_le_bytes().to_vec())
+            .collect::<Vec<u8>>();
+        match read_npy(&npy("<f4", false, "(2,)", &data)[..]) {
+            Err(VolumeError::NotANumber(1)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_volume_filtrations() {
+        // A ring of low values around a high value at the center.
+        let data = [1u8, 1, 1, 1, 9, 1, 1, 1, 1];
+        let dtype = "uint8".parse::<DataType>().unwrap();
+        let volume = read_raw(&data[..], dtype, Endianness::Little, &[3, 3]).unwrap();
+        assert!(read_raw(&data[..], dtype, Endianness::Little, &[2, 3]).is_err());
+
+        let positive = |volume: &Volume, lower_star: bool| {
+            let filt = if lower_star {
+                volume.lower_star_filtration()
+            } else {
+                volume.voxel_filtration()
+            };
+            filt.unwrap()
+                .diagram()
+                .into_iter()
+                .filter(|(_, Persistence(birth, death))| *death != Some(*birth))
+                .collect::<Vec<_>>()
+        };
+
+        let expected = vec![
+            (0, Persistence(1.0, None)),
+            (1, Persistence(1.0, Some(9.0))),
+        ];
+        assert_eq!(volume.voxel_filtration().unwrap().len(), 16 + 24 + 9);
+        assert_eq!(positive(&volume, false), expected);
+        assert_eq!(volume.lower_star_filtration().unwrap().len(), 9 + 12 + 4);
+        assert_eq!(positive(&volume, true), expected);
+    }
+}
@@ -0,0 +1,257 @@
+use crate::distance::Distance;
+use crate::filtration::Filtration;
+use crate::flag::flag_filtration;
+use crate::landmark::GreedyTree;
+use crate::simplex::Simplex;
+use crate::traits::*;
+
+/// Builds the Vietoris-Rips filtration of the metric space.
+///
+/// The filtration value of a simplex is the diameter of its vertices.
+/// The simplices of dimension greater than `max_dim` or with the value greater than
+/// `max_value` are not included, and neither are the pairs of points at infinite distance.
+/// The vertices of the complex are the indices of the points.
+pub fn rips_filtration<D: Distance>(
+    space: &D,
+    max_dim: usize,
+    max_value: f64,
+) -> Filtration<Simplex, f64> {
+    let n = space.len();
+    let mut edges = Vec::new();
+    for u in 0..n {
+        for v in u + 1..n {
+            let d = space.distance(u, v);
+            if d <= max_value && d.is_finite() {
+                edges.push((u, v, d));
+            }
+        }
+    }
+    let labels: Vec<usize> = (0..n).collect();
+    flag_filtration(&labels, &vec![0.0; n], &edges, max_dim, max_value)
+}
+
+/// Builds the sparse Vietoris-Rips filtration of the metric space.
+///
+/// The filtration is built from the greedy permutation of the space as described by
+/// Cavanna, Jahanseir and Sheehy, "A geometric perspective on sparse filtrations" (2015).
+/// A point `p` with the insertion radius `l` has the weight `w(a) = 0` for `a <= l / e`,
+/// `w(a) = a - l / e` for `l / e <= a <= l / (e(1 - e))` and `w(a) = e * a` after that,
+/// where `e = epsilon / (1 + epsilon)`. An edge enters at `2a` for the least `a` such that
+/// its length plus the weights of its endpoints is at most `2a`, and `p` is removed from
+/// the net after `2l / (e(1 - e))`. Since the weights are at most `e * a`, the diagram is
+/// a `1 / (1 - e) = (1 + epsilon)`-approximation of the diagram of `rips_filtration` in
+/// the multiplicative sense.
+/// The size of the filtration is linear in the number of points for a doubling metric of
+/// fixed dimension, and only the pairs of points within the removal radius of the later one
+/// are examined. The filtration values are in the same scale as those of `rips_filtration`.
+pub fn sparse_rips_filtration<D: Distance>(
+    space: &D,
+    max_dim: usize,
+    epsilon: f64,
+) -> Filtration<Simplex, f64> {
+    assert!(epsilon > 0.0);
+
+    let n = space.len();
+    if n == 0 {
+        return Filtration::new();
+    }
+    let e = epsilon / (1.0 + epsilon);
+    let tree = GreedyTree::new(space, 0, n);
+    let mut lambda = vec![0.0; n];
+    for (p, r) in tree.order.iter().zip(tree.radii.iter()) {
+        lambda[*p] = *r;
+    }
+    let deletion: Vec<f64> = lambda.iter().map(|l| 2.0 * l / (e * (1.0 - e))).collect();
+
+    // The later point of an edge has the smaller insertion radius, so it is removed first
+    // and the edge is at most as long as its removal value.
+    let mut edges = Vec::new();
+    for (i, v) in tree.order.iter().enumerate() {
+        for (u, d) in tree.range(space, *v, deletion[*v], Some(i)).into_iter() {
+            if let Some(entry) = edge_entry(d, lambda[u], lambda[*v], e) {
+                if entry <= deletion[*v] {
+                    edges.push((u.min(*v), u.max(*v), entry));
+                }
+            }
+        }
+    }
+
+    // A clique enters the filtration only if all its vertices are alive at that time.
+    let labels: Vec<usize> = (0..n).collect();
+    let filt = flag_filtration(&labels, &vec![0.0; n], &edges, max_dim, f64::INFINITY);
+    let elems = filt
+        .complex
+        .basis
+        .iter()
+        .filter(|(index, simplex)| {
+            simplex
+                .vertices()
+                .all(|v| filt.values[*index] <= deletion[*v])
+        })
+        .map(|(index, simplex)| (simplex.clone(), filt.values[index]))
+        .collect();
+    Filtration::from_unsorted(elems)
+}
+
+/// Returns the weight of a point with the insertion radius `lambda` at the scale `alpha`.
+fn weight(lambda: f64, e: f64, alpha: f64) -> f64 {
+    if alpha <= lambda / e {
+        0.0
+    } else if alpha <= lambda / (e * (1.0 - e)) {
+        alpha - lambda / e
+    } else {
+        e * alpha
+    }
+}
+
+/// Computes the value at which the edge of length `d` enters the sparse filtration.
+///
+/// The edge enters at `2 * alpha` for the least `alpha` such that
+/// `d + weight(lu, alpha) + weight(lv, alpha) <= 2 * alpha`.
+/// The left hand side minus `2 * alpha` is continuous, non-increasing and piecewise linear
+/// in `alpha`, so the root is found on the piece where the sign changes.
+fn edge_entry(d: f64, lu: f64, lv: f64, e: f64) -> Option<f64> {
+    let f = |alpha: f64| d + weight(lu, e, alpha) + weight(lv, e, alpha) - 2.0 * alpha;
+
+    let mut breakpoints: Vec<f64> = [lu, lv]
+        .iter()
+        .flat_map(|l| vec![l / e, l / (e * (1.0 - e))])
+        .filter(|b| b.is_finite())
+        .collect();
+    breakpoints.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+    let mut a = 0.0;
+    if f(a) <= 0.0 {
+        return Some(0.0);
+    }
+    for b in breakpoints.into_iter() {
+        if b <= a {
+            continue;
+        }
+        if f(b) <= 0.0 {
+            let slope = (f(b) - f(a)) / (b - a);
+            return Some(2.0 * (a - f(a) / slope));
+        }
+        a = b;
+    }
+
+    let slope = f(a + 1.0) - f(a);
+    if slope < 0.0 {
+        Some(2.0 * (a - f(a) / slope))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::distance::PointCloud;
+    use crate::rips::{rips_filtration, sparse_rips_filtration};
+    use crate::testing::circle;
+    use crate::Persistence;
+
+    fn longest_bar(diagram: Vec<(usize, Persistence<f64>)>, dim: usize) -> Persistence<f64> {
+        diagram
+            .into_iter()
+            .filter(|(d, _)| *d == dim)
+            .map(|(_, pers)| pers)
+            .max_by(|Persistence(a, x), Persistence(b, y)| {
+                let x = x.unwrap_or(f64::INFINITY) - a;
+                let y = y.unwrap_or(f64::INFINITY) - b;
+                x.partial_cmp(&y).unwrap()
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_rips_circle() {
+        let space = circle(12);
+        let filt = rips_filtration(&space, 2, 3.0);
+        let Persistence(birth, death) = longest_bar(filt.diagram().unwrap(), 1);
+        assert!((birth - 2.0 * (std::f64::consts::PI / 12.0).sin()).abs() < 1e-9);
+        assert!((death.unwrap() - 3.0f64.sqrt()).abs() < 1e-9);
+    }
+
+    /// Checks that the bars of two diagrams in the dimension `dim` are matched so that
+    /// the matched births and deaths are within the factor `c`, and the unmatched bars
+    /// are shorter than the factor `c * c`.
+    fn within_factor(
+        a: &[(usize, Persistence<f64>)],
+        b: &[(usize, Persistence<f64>)],
+        dim: usize,
+        c: f64,
+    ) -> bool {
+        let bars = |diagram: &[(usize, Persistence<f64>)]| -> Vec<(f64, f64)> {
+            diagram
+                .iter()
+                .filter(|(d, Persistence(birth, death))| *d == dim && Some(*birth) != *death)
+                .map(|(_, Persistence(birth, death))| (*birth, death.unwrap_or(f64::INFINITY)))
+                .collect()
+        };
+        let close = |x: f64, y: f64| x == y || (x <= y * c && y <= x * c);
+        let short = |(birth, death): (f64, f64)| death <= birth * c * c;
+        let (a, b) = (bars(a), bars(b));
+        let (n, m) = (a.len(), b.len());
+
+        // The left side is the bars of `a` and the diagonal copies of the bars of `b`,
+        // and the right side is the bars of `b` and the diagonal copies of the bars of `a`.
+        let adjacent = |i: usize, j: usize| match (i < n, j < m) {
+            (true, true) => close(a[i].0, b[j].0) && close(a[i].1, b[j].1),
+            (true, false) => j - m == i && short(a[i]),
+            (false, true) => i - n == j && short(b[j]),
+            (false, false) => true,
+        };
+        let mut matched: Vec<Option<usize>> = vec![None; n + m];
+        fn augment(
+            i: usize,
+            adjacent: &dyn Fn(usize, usize) -> bool,
+            matched: &mut Vec<Option<usize>>,
+            visited: &mut Vec<bool>,
+        ) -> bool {
+            for j in 0..matched.len() {
+                if adjacent(i, j) && !visited[j] {
+                    visited[j] = true;
+                    let free = match matched[j] {
+                        None => true,
+                        Some(k) => augment(k, adjacent, matched, visited),
+                    };
+                    if free {
+                        matched[j] = Some(i);
+                        return true;
+                    }
+                }
+            }
+            false
+        }
+        (0..n + m).all(|i| augment(i, &adjacent, &mut matched, &mut vec![false; n + m]))
+    }
+
+    #[test]
+    fn test_sparse_rips_approximation() {
+        // A noisy ellipse with uneven spacing and a few points inside.
+        let mut points: Vec<Vec<f64>> = (0..28)
+            .map(|i| {
+                let t = i as f64;
+                let theta = 2.0 * std::f64::consts::PI * (t + 0.4 * (1.7 * t).sin()) / 28.0;
+                let r = 1.0 + 0.1 * (2.3 * t).sin();
+                vec![1.6 * r * theta.cos(), r * theta.sin()]
+            })
+            .collect();
+        points.extend((0..4).map(|i| {
+            let t = i as f64;
+            vec![0.3 * (2.9 * t).cos() - 0.2, 0.25 * (1.3 * t).sin()]
+        }));
+        let space = PointCloud::new(points);
+
+        let full = rips_filtration(&space, 2, f64::INFINITY);
+        let diagram = full.diagram().unwrap();
+        for epsilon in &[0.2, 1.0] {
+            let sparse = sparse_rips_filtration(&space, 2, *epsilon);
+            assert!(sparse.len() < full.len());
+            let sparse = sparse.diagram().unwrap();
+            let c = (1.0 + epsilon) * (1.0 + 1e-9);
+            assert!(within_factor(&diagram, &sparse, 0, c));
+            assert!(within_factor(&diagram, &sparse, 1, c));
+        }
+    }
+}
@@ -14,6 +14,8 @@ macro_rules! simplex {
 ///
 /// This struct represents a simplex, which is a set of vertices.
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "SimplexData"))]
 pub struct Simplex {
     /// The vertices ordered in ascending order.
     vertices: Vec<usize>,
@@ -44,6 +46,31 @@ impl Simplex {
     }
 }
 
+/// The unchecked form of `Simplex` read by the deserializer
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct SimplexData {
+    vertices: Vec<usize>,
+}
+
+#[cfg(feature = "serde")]
+impl std::convert::TryFrom<SimplexData> for Simplex {
+    type Error = &'static str;
+
+    /// Accepts the vertices only if they are non-empty and strictly ascending.
+    fn try_from(data: SimplexData) -> Result<Simplex, Self::Error> {
+        if data.vertices.is_empty() {
+            Err("simplex has no vertices")
+        } else if data.vertices.windows(2).any(|w| w[0] >= w[1]) {
+            Err("vertices of simplex are not strictly ascending")
+        } else {
+            Ok(Simplex {
+                vertices: data.vertices,
+            })
+        }
+    }
+}
+
 impl<'a> ChainGeneratorVertices<'a> for Simplex {
     type VerticesIter = Vertices<'a>;
 
@@ -210,4 +237,17 @@ mod tests {
         assert!(s.is_face_of(s));
         assert!(t.is_face_of(t));
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_simplex_serde() {
+        let s = Simplex::new(vec![3, 0, 5]);
+        let json = serde_json::to_string(&s).unwrap();
+        assert_eq!(json, r#"{"vertices":[0,3,5]}"#);
+        assert_eq!(serde_json::from_str::<Simplex>(&json).unwrap(), s);
+
+        assert!(serde_json::from_str::<Simplex>(r#"{"vertices":[]}"#).is_err());
+        assert!(serde_json::from_str::<Simplex>(r#"{"vertices":[2,1]}"#).is_err());
+        assert!(serde_json::from_str::<Simplex>(r#"{"vertices":[1,1]}"#).is_err());
+    }
 }
@@ -0,0 +1,61 @@
+//! The fixtures and helpers shared by the tests
+
+use crate::complex::Complex;
+use crate::distance::PointCloud;
+use crate::indexed_vec::IndexedVec;
+use crate::pair::Pair;
+use crate::simplex;
+use crate::simplex::Simplex;
+use crate::traits::*;
+use crate::z2reduce::Z2ColumnReduce;
+use crate::z2vector::Z2VectorVec;
+
+/// Returns `n` points evenly spaced on the unit circle.
+pub fn circle_points(n: usize) -> Vec<Vec<f64>> {
+    (0..n)
+        .map(|i| {
+            let theta = 2.0 * std::f64::consts::PI * i as f64 / n as f64;
+            vec![theta.cos(), theta.sin()]
+        })
+        .collect()
+}
+
+/// Returns the point cloud of `circle_points`.
+pub fn circle(n: usize) -> PointCloud {
+    PointCloud::new(circle_points(n))
+}
+
+/// Returns the boundary of a triangle, i.e. the smallest simplicial circle.
+///
+/// The simplices are `0`, `1`, `2`, `0 1`, `1 2` and `0 2` in this order.
+pub fn triangle_boundary() -> Complex<IndexedVec<Simplex>, Simplex> {
+    let mut complex = Complex::new();
+    for simplex in vec![
+        simplex![0],
+        simplex![1],
+        simplex![2],
+        simplex![0, 1],
+        simplex![1, 2],
+        simplex![0, 2],
+    ]
+    .into_iter()
+    {
+        complex.push(simplex).unwrap();
+    }
+    complex
+}
+
+/// Computes the Betti numbers over Z/2 up to the dimension `dim`.
+pub fn betti_numbers<Gen>(complex: &Complex<IndexedVec<Gen>, Gen>, dim: usize) -> Vec<usize>
+where
+    Gen: 'static + PartialEq + ChainGenerator + for<'a> ChainGeneratorBoundary<'a, Gen>,
+{
+    let reduce = Z2ColumnReduce::<Z2VectorVec>::from_complex(complex).unwrap();
+    let mut betti = vec![0; dim + 1];
+    for (pers, _) in Pair::new(&reduce, reduce.cycles()) {
+        if pers.is_essential() {
+            betti[complex.basis[pers.0].dimension()] += 1;
+        }
+    }
+    betti
+}
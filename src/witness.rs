@@ -0,0 +1,159 @@
+use crate::distance::Distance;
+use crate::filtration::{compare_values, Filtration};
+use crate::flag::flag_filtration;
+use crate::simplex::Simplex;
+
+/// Builds the witness filtration with the landmarks.
+///
+/// Every point of `space` is a witness. Let `m(w)` be the distance from a witness `w` to
+/// its `nu`-th nearest landmark (`m(w) = 0` if `nu` is zero).
+/// The filtration value of a simplex is the minimum over the witnesses `w` of
+/// `max(d(w, l) - m(w), 0)`, where `l` runs over the vertices of the simplex.
+/// The simplices of dimension greater than `max_dim` or with the value greater than
+/// `max_value` are not included. The vertices of the complex are the indices of the landmarks
+/// in `space`.
+pub fn witness_filtration<D: Distance>(
+    space: &D,
+    landmarks: &[usize],
+    nu: usize,
+    max_dim: usize,
+    max_value: f64,
+) -> Filtration<Simplex, f64> {
+    let witness = Witnesses::new(space, landmarks, nu);
+
+    let mut elems = Vec::new();
+    let mut stack: Vec<Vec<usize>> = (0..landmarks.len()).rev().map(|l| vec![l]).collect();
+
+    while let Some(simplex) = stack.pop() {
+        let value = witness.value(&simplex);
+        if value > max_value {
+            continue;
+        }
+        if simplex.len() <= max_dim {
+            let last = *simplex.last().unwrap();
+            for l in (last + 1..landmarks.len()).rev() {
+                let mut coface = simplex.clone();
+                coface.push(l);
+                stack.push(coface);
+            }
+        }
+        let vertices = simplex.into_iter().map(|l| landmarks[l]).collect();
+        elems.push((Simplex::new(vertices), value));
+    }
+
+    Filtration::from_unsorted(elems)
+}
+
+/// Builds the lazy witness filtration with the landmarks.
+///
+/// The filtration is the flag filtration whose vertex and edge values are those of
+/// the witness filtration. See `witness_filtration` for the parameters.
+pub fn lazy_witness_filtration<D: Distance>(
+    space: &D,
+    landmarks: &[usize],
+    nu: usize,
+    max_dim: usize,
+    max_value: f64,
+) -> Filtration<Simplex, f64> {
+    let witness = Witnesses::new(space, landmarks, nu);
+
+    let vertex_values: Vec<f64> = (0..landmarks.len()).map(|l| witness.value(&[l])).collect();
+
+    let mut edges = Vec::new();
+    for a in 0..landmarks.len() {
+        for b in a + 1..landmarks.len() {
+            edges.push((a, b, witness.value(&[a, b])));
+        }
+    }
+
+    flag_filtration(landmarks, &vertex_values, &edges, max_dim, max_value)
+}
+
+struct Witnesses {
+    /// The distances from the witnesses to each landmark.
+    distances: Vec<Vec<f64>>,
+    /// The distance from each witness to its `nu`-th nearest landmark.
+    offsets: Vec<f64>,
+}
+
+impl Witnesses {
+    fn new<D: Distance>(space: &D, landmarks: &[usize], nu: usize) -> Self {
+        assert!(nu <= landmarks.len());
+        let distances: Vec<Vec<f64>> = landmarks
+            .iter()
+            .map(|l| (0..space.len()).map(|w| space.distance(w, *l)).collect())
+            .collect();
+        let offsets = (0..space.len())
+            .map(|w| {
+                if nu == 0 {
+                    0.0
+                } else {
+                    let mut column: Vec<f64> = distances.iter().map(|d| d[w]).collect();
+                    column.sort_by(compare_values);
+                    column[nu - 1]
+                }
+            })
+            .collect();
+        Witnesses {
+            distances: distances,
+            offsets: offsets,
+        }
+    }
+
+    /// Returns the value of a simplex given by the indices of its landmarks.
+    fn value(&self, simplex: &[usize]) -> f64 {
+        self.offsets
+            .iter()
+            .enumerate()
+            .map(|(w, m)| {
+                let farthest = simplex
+                    .iter()
+                    .map(|l| self.distances[*l][w])
+                    .fold(f64::NEG_INFINITY, f64::max);
+                (farthest - m).max(0.0)
+            })
+            .fold(f64::INFINITY, f64::min)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::filtration::Filtration;
+    use crate::landmark::maxmin_landmarks;
+    use crate::simplex::Simplex;
+    use crate::testing::circle;
+    use crate::traits::*;
+    use crate::witness::{lazy_witness_filtration, witness_filtration};
+    use crate::Persistence;
+
+    fn longest_bar(diagram: Vec<(usize, Persistence<f64>)>, dim: usize) -> f64 {
+        diagram
+            .into_iter()
+            .filter(|(d, _)| *d == dim)
+            .map(|(_, Persistence(birth, death))| death.unwrap_or(f64::INFINITY) - birth)
+            .fold(0.0, f64::max)
+    }
+
+    #[test]
+    fn test_witness_circle() {
+        let space = circle(60);
+        let landmarks = maxmin_landmarks(&space, 8, 0);
+
+        let filt = witness_filtration(&space, &landmarks, 1, 2, 2.0);
+        assert!(longest_bar(filt.diagram().unwrap(), 1) > 0.5);
+
+        let lazy = lazy_witness_filtration(&space, &landmarks, 1, 2, 2.0);
+        assert!(longest_bar(lazy.diagram().unwrap(), 1) > 0.5);
+
+        // Both filtrations have the same vertices and edges.
+        let skeleton = |filt: &Filtration<Simplex, f64>| {
+            filt.complex
+                .basis
+                .iter()
+                .filter(|(_, s)| s.dimension() <= 1)
+                .map(|(index, s)| (s.clone(), filt.values[index]))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(skeleton(&filt), skeleton(&lazy));
+    }
+}
@@ -8,12 +8,58 @@ use std::iter::FromIterator;
 use std::marker::PhantomData;
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(
+        try_from = "Z2ColumnReduceData<V>",
+        bound(
+            serialize = "V: serde::Serialize",
+            deserialize = "V: serde::Deserialize<'de> + Z2Vector"
+        )
+    )
+)]
 pub struct Z2ColumnReduce<V> {
     reduced: IndexedVec<V>,
     // mapping of lowest index to position in `reduced`
     lowest_memo: BTreeMap<usize, usize>,
 }
 
+/// The unchecked form of `Z2ColumnReduce` read by the deserializer
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct Z2ColumnReduceData<V> {
+    reduced: IndexedVec<V>,
+    lowest_memo: BTreeMap<usize, usize>,
+}
+
+#[cfg(feature = "serde")]
+impl<V: Z2Vector> std::convert::TryFrom<Z2ColumnReduceData<V>> for Z2ColumnReduce<V> {
+    type Error = &'static str;
+
+    /// Accepts the memo only if it maps the lowest index of every nonzero column to
+    /// the position of the column, and nothing else.
+    fn try_from(data: Z2ColumnReduceData<V>) -> Result<Z2ColumnReduce<V>, Self::Error> {
+        let lowests = data
+            .reduced
+            .iter()
+            .filter_map(|(pos, column)| column.lowest().map(|lowest| (lowest, pos)))
+            .collect::<Vec<_>>();
+        if lowests.len() != data.lowest_memo.len()
+            || lowests
+                .iter()
+                .any(|(lowest, pos)| data.lowest_memo.get(lowest) != Some(pos))
+        {
+            Err("lowest_memo does not match the reduced columns")
+        } else {
+            Ok(Z2ColumnReduce {
+                reduced: data.reduced,
+                lowest_memo: data.lowest_memo,
+            })
+        }
+    }
+}
+
 impl<V> Z2ColumnReduce<V>
 where
     V: Z2Vector + std::fmt::Debug,
@@ -200,3 +246,61 @@ impl CyclePositions {
         self.positions.iter().map(|pos| (*pos, ()))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        use crate::complex::Complex;
+        use crate::indexed_vec::IndexedVec;
+        use crate::pair::Pair;
+        use crate::simplex;
+        use crate::simplex::Simplex;
+        use crate::traits::*;
+        use crate::z2reduce::Z2ColumnReduce;
+        use crate::z2vector::{Z2Chain, Z2VectorVec};
+        use crate::Persistence;
+
+        type Reduce = Z2ColumnReduce<Z2Chain<Z2VectorVec>>;
+
+        fn pairs(reduce: &Reduce) -> Vec<Persistence<usize>> {
+            Pair::new(reduce, reduce.cycles())
+                .map(|(pers, _)| pers)
+                .collect()
+        }
+
+        let mut complex = Complex::<IndexedVec<Simplex>, Simplex>::new();
+        for s in vec![
+            simplex![0],
+            simplex![1],
+            simplex![2],
+            simplex![0, 1],
+            simplex![1, 2],
+            simplex![0, 2],
+            simplex![0, 1, 2],
+        ] {
+            complex.push(s).unwrap();
+        }
+
+        let reduce =
+            Reduce::from_complex_with(&complex, |index, chain| Z2Chain::new(index, chain)).unwrap();
+        let json = serde_json::to_string(&reduce).unwrap();
+        let read: Reduce = serde_json::from_str(&json).unwrap();
+        assert_eq!(pairs(&read), pairs(&reduce));
+
+        let json = serde_json::to_string(&pairs(&reduce)).unwrap();
+        assert_eq!(json, "[[0,null],[1,3],[2,4],[5,6]]");
+
+        let read: Z2ColumnReduce<Z2VectorVec> = serde_json::from_str(
+            r#"{"reduced":{"vec":[{"vec":[]},{"vec":[]},{"vec":[1,0]}],"start":0},"lowest_memo":{"1":2}}"#,
+        )
+        .unwrap();
+        assert_eq!(read.lookup_by_lowest(1), Some(2));
+        let past_end =
+            r#"{"reduced":{"vec":[{"vec":[]},{"vec":[]},{"vec":[1,0]}],"start":0},"lowest_memo":{"1":5}}"#;
+        assert!(serde_json::from_str::<Z2ColumnReduce<Z2VectorVec>>(past_end).is_err());
+        let missing = r#"{"reduced":{"vec":[{"vec":[]},{"vec":[]},{"vec":[1,0]}],"start":0},"lowest_memo":{}}"#;
+        assert!(serde_json::from_str::<Z2ColumnReduce<Z2VectorVec>>(missing).is_err());
+    }
+}
@@ -20,6 +20,7 @@ pub trait Z2VectorIter<'a> {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Z2Chain<V> {
     pub chain: V,
     pub image: V,
@@ -52,6 +53,8 @@ where
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "Z2VectorVecData"))]
 pub struct Z2VectorVec {
     // the elements must be sorted in the descending order.
     vec: Vec<usize>,
@@ -77,6 +80,28 @@ impl Z2VectorVec {
     }
 }
 
+/// The unchecked form of `Z2VectorVec` read by the deserializer
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct Z2VectorVecData {
+    vec: Vec<usize>,
+}
+
+#[cfg(feature = "serde")]
+impl std::convert::TryFrom<Z2VectorVecData> for Z2VectorVec {
+    type Error = &'static str;
+
+    /// Accepts the elements only if they are strictly descending.
+    fn try_from(data: Z2VectorVecData) -> Result<Z2VectorVec, Self::Error> {
+        let vector = Z2VectorVec { vec: data.vec };
+        if vector.is_valid() {
+            Ok(vector)
+        } else {
+            Err("elements of Z2VectorVec are not strictly descending")
+        }
+    }
+}
+
 impl Z2Vector for Z2VectorVec {
     #[inline]
     fn lowest(&self) -> Option<usize> {
@@ -204,4 +229,16 @@ mod tests {
         assert!(x.is_valid());
         assert_eq!(x, y);
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    pub fn z2vecvec_serde() {
+        let x: Z2VectorVec = vec![0, 2, 5, 6].into();
+        let json = serde_json::to_string(&x).unwrap();
+        assert_eq!(json, r#"{"vec":[6,5,2,0]}"#);
+        assert_eq!(serde_json::from_str::<Z2VectorVec>(&json).unwrap(), x);
+
+        assert!(serde_json::from_str::<Z2VectorVec>(r#"{"vec":[0,2]}"#).is_err());
+        assert!(serde_json::from_str::<Z2VectorVec>(r#"{"vec":[2,2]}"#).is_err());
+    }
 }